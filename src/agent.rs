@@ -0,0 +1,29 @@
+pub mod runner;
+pub mod simple;
+pub mod solver;
+
+use crate::logic::{visible::VisibleBoard, Minesweeper};
+
+// A move a player can make
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Move {
+	Reveal(usize, usize),
+	// Cycles the tile's marker, as a right click does
+	Flag(usize, usize),
+	Chord(usize, usize),
+}
+
+impl Move {
+	pub fn play(self, game: &mut Minesweeper) {
+		match self {
+			Move::Reveal(x, y) => game.reveal(x, y),
+			Move::Flag(x, y) => game.modify(x, y),
+			Move::Chord(x, y) => game.chord(x, y),
+		}
+	}
+}
+
+// Something which plays Minesweeper, seeing only what a player would see.
+pub trait Agent {
+	fn choose_move(&mut self, board: &VisibleBoard) -> Move;
+}
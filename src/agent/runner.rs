@@ -0,0 +1,178 @@
+use std::{error::Error, fmt};
+
+use crate::logic::{first_click::FirstClick, visible::VisibleBoard, GameState, Minesweeper};
+
+use super::Agent;
+
+pub const DEFAULT_GAMES: usize = 100;
+// Games are given up after this many moves per tile, in case an agent keeps making moves which change nothing
+const MOVES_PER_TILE: usize = 4;
+
+#[derive(Clone, Copy, Debug)]
+pub struct GameResult {
+	pub seed: u64,
+	pub won: bool,
+	// Every move counts, including flags and moves which did nothing
+	pub moves: usize,
+	pub bbbv: usize,
+}
+
+// How an agent did over a run of games on one board size
+pub struct RunStats {
+	pub board: (usize, usize, usize),
+	pub first_click: FirstClick,
+	pub games: Vec<GameResult>,
+}
+
+impl RunStats {
+	pub fn wins(&self) -> usize {
+		self.games.iter().filter(|game| game.won).count()
+	}
+
+	pub fn win_rate(&self) -> f64 {
+		self.wins() as f64 / self.games.len().max(1) as f64
+	}
+
+	fn mean(&self, won_only: bool, value: impl Fn(&GameResult) -> f64) -> f64 {
+		let games: Vec<f64> = self.games.iter().filter(|game| game.won || !won_only).map(value).collect();
+		games.iter().sum::<f64>() / games.len().max(1) as f64
+	}
+}
+
+impl fmt::Display for RunStats {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let (width, height, mines) = self.board;
+		let first_seed = self.games.first().map_or(0, |game| game.seed);
+		writeln!(
+			f,
+			"{} games on a {}x{} board with {} mines, first click rule '{}', seeds {} to {}",
+			self.games.len(),
+			width,
+			height,
+			mines,
+			self.first_click.name(),
+			first_seed,
+			first_seed.wrapping_add(self.games.len().saturating_sub(1) as u64)
+		)?;
+		writeln!(f, "won:   {} ({:.1}%)", self.wins(), self.win_rate() * 100.0)?;
+		writeln!(
+			f,
+			"moves: {:.1} per game, {:.1} per won game",
+			self.mean(false, |game| game.moves as f64),
+			self.mean(true, |game| game.moves as f64)
+		)?;
+		let bbbv = self.games.iter().map(|game| game.bbbv);
+		write!(
+			f,
+			"3BV:   {:.1} per game (min {}, max {}), {:.1} per won game, {:.2} per move in won games",
+			self.mean(false, |game| game.bbbv as f64),
+			bbbv.clone().min().unwrap_or_default(),
+			bbbv.max().unwrap_or_default(),
+			self.mean(true, |game| game.bbbv as f64),
+			self.mean(true, |game| game.bbbv as f64 / game.moves.max(1) as f64)
+		)
+	}
+}
+
+// Plays one game on a board generated from `seed`, until it is won or lost.
+pub fn play_game(
+	agent: &mut dyn Agent,
+	board: (usize, usize, usize),
+	first_click: FirstClick,
+	seed: u64,
+) -> Result<GameResult, Box<dyn Error>> {
+	let (width, height, mines) = board;
+	let mut game = Minesweeper::new(width, height, mines, first_click)?;
+	game.board.seed = Some(seed);
+	let mut moves = 0;
+	while matches!(game.state, GameState::Empty | GameState::Playing) && moves < width * height * MOVES_PER_TILE {
+		agent.choose_move(&VisibleBoard::from(&game.board)).play(&mut game);
+		// No one is listening
+		game.events.clear();
+		moves += 1;
+	}
+	Ok(GameResult {
+		seed,
+		won: game.state == GameState::Victory,
+		moves,
+		bbbv: game.board.layout().bbbv(),
+	})
+}
+
+// Plays `games` games, on boards from consecutive seeds starting at `seed`.
+pub fn run(
+	agent: &mut dyn Agent,
+	board: (usize, usize, usize),
+	first_click: FirstClick,
+	games: usize,
+	seed: u64,
+) -> Result<RunStats, Box<dyn Error>> {
+	let games = (0..games as u64)
+		.map(|game| play_game(agent, board, first_click, seed.wrapping_add(game)))
+		.collect::<Result<_, _>>()?;
+	Ok(RunStats {
+		board,
+		first_click,
+		games,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::agent::{simple::SimpleAgent, Move};
+
+	// Always makes the same move
+	struct Repeat(Move);
+
+	impl Agent for Repeat {
+		fn choose_move(&mut self, _: &VisibleBoard) -> Move {
+			self.0
+		}
+	}
+
+	fn results(stats: &RunStats) -> Vec<(u64, bool, usize, usize)> {
+		stats.games.iter().map(|game| (game.seed, game.won, game.moves, game.bbbv)).collect()
+	}
+
+	#[test]
+	fn runs_on_fixed_seeds_repeat_exactly() {
+		let board = (9, 9, 10);
+		let stats = run(&mut SimpleAgent::new(1), board, FirstClick::default(), 40, 1).unwrap();
+		let again = run(&mut SimpleAgent::new(1), board, FirstClick::default(), 40, 1).unwrap();
+		assert_eq!(results(&stats), results(&again));
+
+		assert_eq!(
+			stats.games.iter().map(|game| game.seed).collect::<Vec<_>>(),
+			(1..=40).collect::<Vec<_>>()
+		);
+		assert!(stats.wins() > 0 && stats.wins() < 40);
+		assert_eq!(stats.win_rate(), stats.wins() as f64 / 40.0);
+		// 3BV is at least one click and at most one per safe tile
+		assert!(stats.games.iter().all(|game| (1..=71).contains(&game.bbbv)));
+		// Winning takes a move for every click of the board's 3BV, at least
+		assert!(stats.games.iter().filter(|game| game.won).all(|game| game.moves >= game.bbbv));
+		let summary = stats.to_string();
+		assert!(summary.starts_with("40 games on a 9x9 board with 10 mines, first click rule 'opening', seeds 1 to 40\n"));
+		assert!(summary.contains(&format!("won:   {} ({:.1}%)", stats.wins(), stats.win_rate() * 100.0)));
+	}
+
+	#[test]
+	fn a_reveal_wins_boards_of_a_3bv_of_one() {
+		// With a single mine most boards are one opening, but a mine beside a corner cuts the corner off
+		let stats = run(&mut Repeat(Move::Reveal(4, 4)), (9, 9, 1), FirstClick::default(), 5, 0).unwrap();
+		assert!(stats.wins() > 0);
+		for game in &stats.games {
+			assert_eq!(game.won, game.bbbv == 1);
+			assert_eq!(game.moves, if game.won { 1 } else { 9 * 9 * MOVES_PER_TILE });
+		}
+	}
+
+	#[test]
+	fn agents_which_get_nowhere_are_given_up_on() {
+		// Flags can't be placed before the first reveal, so the game never starts
+		let stats = run(&mut Repeat(Move::Flag(0, 0)), (9, 9, 10), FirstClick::default(), 2, 0).unwrap();
+		assert!(stats.games.iter().all(|game| !game.won && game.moves == 9 * 9 * MOVES_PER_TILE));
+		assert_eq!(stats.win_rate(), 0.0);
+	}
+}
@@ -0,0 +1,70 @@
+use crate::{
+	logic::visible::{VisibleBoard, VisibleTile},
+	util::Rng,
+};
+
+use super::{Agent, Move};
+
+// Flags and chords around a number when that number alone makes it certain, and otherwise reveals a random
+// hidden tile.
+pub struct SimpleAgent {
+	rng: Rng,
+}
+
+impl SimpleAgent {
+	pub fn new(seed: u64) -> Self {
+		Self { rng: Rng::new(seed) }
+	}
+}
+
+impl Agent for SimpleAgent {
+	fn choose_move(&mut self, board: &VisibleBoard) -> Move {
+		let mut hidden_tiles = vec![];
+		let mut flags = 0;
+		for (x, y, tile) in board.tiles() {
+			let adjacent = match tile {
+				VisibleTile::Hidden => {
+					hidden_tiles.push((x, y));
+					continue;
+				}
+				VisibleTile::Flagged => {
+					flags += 1;
+					continue;
+				}
+				VisibleTile::Revealed(adjacent) if adjacent > 0 => adjacent as usize,
+				_ => continue,
+			};
+			let mut adjacent_flags = 0;
+			let mut hidden = vec![];
+			for (x, y) in board.neighbours(x, y) {
+				match board.get(x, y) {
+					Some(VisibleTile::Flagged) => adjacent_flags += 1,
+					Some(VisibleTile::Hidden) => hidden.push((x, y)),
+					_ => (),
+				}
+			}
+			if hidden.is_empty() {
+				continue;
+			}
+			if adjacent_flags == adjacent {
+				return Move::Chord(x, y);
+			}
+			if adjacent_flags + hidden.len() == adjacent {
+				let (x, y) = hidden[0];
+				return Move::Flag(x, y);
+			}
+		}
+		if hidden_tiles.is_empty() {
+			// Everything left is marked, so one of the marks must be wrong
+			let marked = board.tiles().find(|(_, _, tile)| matches!(tile, VisibleTile::Flagged | VisibleTile::Questioned));
+			return marked.map_or(Move::Reveal(0, 0), |(x, y, _)| Move::Flag(x, y));
+		}
+		// Once every mine is flagged whatever is left is safe, else it's a guess
+		let (x, y) = if flags == board.mines() {
+			hidden_tiles[0]
+		} else {
+			hidden_tiles[self.rng.below(hidden_tiles.len())]
+		};
+		Move::Reveal(x, y)
+	}
+}
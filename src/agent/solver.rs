@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use crate::logic::{
+	visible::{VisibleBoard, VisibleTile},
+	GameState, Minesweeper,
+};
+
+// What can be worked out for certain from a board, without guessing. Flags and question marks are the player's
+// opinion rather than anything the board says, so marked tiles are solved like any other hidden tile.
+#[derive(Default, Debug)]
+pub struct Deductions {
+	pub safe: Vec<(usize, usize)>,
+	pub mines: Vec<(usize, usize)>,
+}
+
+impl Deductions {
+	pub fn is_empty(&self) -> bool {
+		self.safe.is_empty() && self.mines.is_empty()
+	}
+}
+
+// Some number of mines among a group of unknown tiles, sorted by position
+struct Constraint {
+	tiles: Vec<(usize, usize)>,
+	mines: usize,
+}
+
+fn is_unknown(tile: VisibleTile) -> bool {
+	matches!(tile, VisibleTile::Hidden | VisibleTile::Flagged | VisibleTile::Questioned)
+}
+
+// Finds every tile one number settles, every tile settled by the difference between two numbers whose tiles
+// overlap, and the tiles settled by the total mine count, until none of them finds anything more.
+pub fn deduce(board: &VisibleBoard) -> Deductions {
+	// Whether each tile worked out so far is a mine
+	let mut known: HashMap<(usize, usize), bool> = HashMap::new();
+	loop {
+		let constraints = constraints(board, &known);
+		let mut found = vec![];
+		for constraint in &constraints {
+			settle(&constraint.tiles, constraint.mines, &mut found);
+		}
+		// Only numbers sharing a tile can say anything together
+		let mut sharing: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+		for (n, constraint) in constraints.iter().enumerate() {
+			for &tile in &constraint.tiles {
+				sharing.entry(tile).or_default().push(n);
+			}
+		}
+		for (n, inner) in constraints.iter().enumerate() {
+			let Some(others) = sharing.get(&inner.tiles[0]) else {
+				continue;
+			};
+			for outer in others.iter().filter(|&&other| other != n).map(|&other| &constraints[other]) {
+				if outer.tiles.len() <= inner.tiles.len() || !inner.tiles.iter().all(|tile| outer.tiles.contains(tile)) {
+					continue;
+				}
+				let rest: Vec<(usize, usize)> = outer.tiles.iter().copied().filter(|tile| !inner.tiles.contains(tile)).collect();
+				settle(&rest, outer.mines.saturating_sub(inner.mines), &mut found);
+			}
+		}
+		if found.is_empty() {
+			let unknown: Vec<(usize, usize)> = board
+				.tiles()
+				.filter(|&(x, y, tile)| is_unknown(tile) && !known.contains_key(&(x, y)))
+				.map(|(x, y, _)| (x, y))
+				.collect();
+			let exploded = board.tiles().filter(|(_, _, tile)| *tile == VisibleTile::Exploded).count();
+			let known_mines = known.values().filter(|&&mine| mine).count();
+			settle(&unknown, board.mines().saturating_sub(exploded + known_mines), &mut found);
+		}
+		let before = known.len();
+		known.extend(found);
+		if known.len() == before {
+			break;
+		}
+	}
+	let mut deductions = Deductions::default();
+	for (tile, mine) in known {
+		if mine {
+			deductions.mines.push(tile);
+		} else {
+			deductions.safe.push(tile);
+		}
+	}
+	// Row by row, so moves are made in a predictable order
+	deductions.safe.sort_by_key(|&(x, y)| (y, x));
+	deductions.mines.sort_by_key(|&(x, y)| (y, x));
+	deductions
+}
+
+// Reveals every tile which can be proven safe, as a player who never guesses would, until the game is won or only
+// guesses are left. Meant for games no one is watching, as their events are thrown away. Whether it won.
+pub fn play_safe(game: &mut Minesweeper) -> bool {
+	while game.state == GameState::Playing {
+		let revealed = game.board.revealed_tiles;
+		for (x, y) in deduce(&VisibleBoard::from(&game.board)).safe {
+			game.reveal(x, y);
+		}
+		game.events.clear();
+		if game.board.revealed_tiles == revealed {
+			break;
+		}
+	}
+	game.state == GameState::Victory
+}
+
+// Tiles which are all safe or all mines, going by how many mines they hold
+fn settle(tiles: &[(usize, usize)], mines: usize, found: &mut Vec<((usize, usize), bool)>) {
+	if mines == 0 || mines == tiles.len() {
+		found.extend(tiles.iter().map(|&tile| (tile, mines > 0)));
+	}
+}
+
+// The unknown tiles next to a revealed number, which is where a guess has to be made once nothing is certain
+pub fn frontier(board: &VisibleBoard) -> Vec<(usize, usize)> {
+	board
+		.tiles()
+		.filter(|&(x, y, tile)| {
+			tile != VisibleTile::Flagged
+				&& is_unknown(tile)
+				&& board.neighbours(x, y).any(|(x, y)| matches!(board.get(x, y), Some(VisibleTile::Revealed(_))))
+		})
+		.map(|(x, y, _)| (x, y))
+		.collect()
+}
+
+// What each revealed number says about the tiles around it which aren't known yet
+fn constraints(board: &VisibleBoard, known: &HashMap<(usize, usize), bool>) -> Vec<Constraint> {
+	let mut constraints = vec![];
+	for (x, y, tile) in board.tiles() {
+		let VisibleTile::Revealed(adjacent) = tile else {
+			continue;
+		};
+		let mut mines = adjacent as usize;
+		let mut tiles = vec![];
+		for (x, y) in board.neighbours(x, y) {
+			match (board.get(x, y), known.get(&(x, y))) {
+				(Some(VisibleTile::Exploded), _) | (_, Some(true)) => mines = mines.saturating_sub(1),
+				(Some(tile), None) if is_unknown(tile) => tiles.push((x, y)),
+				_ => (),
+			}
+		}
+		if !tiles.is_empty() {
+			tiles.sort_by_key(|&(x, y)| (y, x));
+			constraints.push(Constraint { tiles, mines });
+		}
+	}
+	constraints
+}
@@ -0,0 +1,35 @@
+// Pairs up players for races, see `net::relay`. Usage: `relay [ADDRESS] [WIDTH HEIGHT MINES [FIRST_CLICK]]`.
+use std::{error::Error, net::TcpListener};
+
+use minesweeper::{
+	logic::first_click::FirstClick,
+	net::{protocol::DEFAULT_ADDRESS, relay},
+};
+
+// Expert, unless another board is given
+const DEFAULT_BOARD: (usize, usize, usize) = (30, 16, 99);
+
+fn main() -> Result<(), Box<dyn Error>> {
+	let args: Vec<String> = std::env::args().skip(1).collect();
+	let address = args.first().map_or(DEFAULT_ADDRESS, String::as_str);
+	let (board, first_click) = match &args[1.min(args.len())..] {
+		[] => (DEFAULT_BOARD, FirstClick::default()),
+		[width, height, mines] => ((width.parse()?, height.parse()?, mines.parse()?), FirstClick::default()),
+		[width, height, mines, first_click] => ((width.parse()?, height.parse()?, mines.parse()?), first_click.parse()?),
+		_ => return Err("Usage: relay [ADDRESS] [WIDTH HEIGHT MINES [FIRST_CLICK]]".into()),
+	};
+	relay::check_board(board, first_click)?;
+	let (width, height, mines) = board;
+
+	let listener = TcpListener::bind(address)?;
+	println!(
+		"Relay listening on {} with {}x{} boards, {} mines and the '{}' first click rule",
+		address,
+		width,
+		height,
+		mines,
+		first_click.name()
+	);
+	relay::serve(listener, board, first_click);
+	Ok(())
+}
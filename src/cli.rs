@@ -0,0 +1,313 @@
+use std::{error::Error, fmt, iter::Peekable, path::PathBuf, str::FromStr};
+
+use crate::{
+	agent::{
+		runner::{self, DEFAULT_GAMES},
+		simple::SimpleAgent,
+		solver,
+	},
+	logic::{analysis, first_click::FirstClick, game_board::GameBoard, preset::Preset, visible::VisibleBoard, Minesweeper},
+	net::{coop_server, race},
+	util::time_seed,
+};
+
+pub const USAGE: &str = "\
+Usage: minesweeper [OPTIONS] [COMMAND]
+
+Without a command the game opens in a window. Commands run without one:
+  generate         Prints the board a seed gives for a first click in the centre, as a board file
+  solve FILE       Plays a board file from its first opening without guessing, and says how far that gets
+  bench            Has the built in agent play a run of games, and prints how it did
+  analyze          Generates many boards and prints statistics on them, to check how boards are generated
+  race-bot [ADDR]  Races through the relay with a simple bot making the moves
+  coop-server [ADDR]
+                   Holds a board for co-op players to share, of the size the board options give
+
+Addresses are given as HOST:PORT, and left out for 127.0.0.1:7878.
+
+Options:
+  --preset NAME    Board of beginner, intermediate or expert, the default
+  --width N        Board width, in place of the preset's
+  --height N       Board height, in place of the preset's
+  --mines N        Mine count, in place of the preset's
+  --first-click R  What keeps the first reveal safe: none, safe (a mine there is moved to the top left),
+                   opening (no mines around it, the default) or 5x5 (no mines within two tiles of it)
+  --seed N         Seed the board is generated from, else one is taken from the clock. For bench and analyze,
+                   the first game's seed, else 0
+  --games N        Games bench plays, else 100, or boards analyze generates, else 1000
+  --window WxH     Window size in pixels
+  --theme DIR      Theme directory to draw the game with
+  --open FILE      Board file, or replay if it ends in .rawvf, to start with
+  --race [ADDR]    Races another player on the same board through the relay
+  --coop [ADDR]    Joins the board a co-op server shares
+  --broadcast [ADDR]
+                   Streams the game to spectators
+  --spectate [ADDR]
+                   Watches a broadcast game
+  --log-events     Prints every game event to the terminal
+  --frame-bench [N]
+                   Draws N frames, else 600, of the largest board as fast as it can and prints how long they took
+  --help           Prints this message";
+
+// Boards analyze generates, unless told otherwise
+const DEFAULT_BOARDS: usize = 1000;
+// Frames the frame benchmark measures, unless told otherwise
+const DEFAULT_FRAMES: usize = 600;
+
+#[derive(Default, PartialEq, Debug)]
+pub enum Command {
+	#[default]
+	Play,
+	Generate,
+	Solve(PathBuf),
+	Bench,
+	Analyze,
+	RaceBot(String),
+	CoopServer(String),
+	Help,
+}
+
+// What the command line asks for
+#[derive(Default, Debug)]
+pub struct Options {
+	pub command: Command,
+	preset: Option<Preset>,
+	width: Option<usize>,
+	height: Option<usize>,
+	mines: Option<usize>,
+	pub first_click: FirstClick,
+	pub seed: Option<u64>,
+	games: Option<usize>,
+	pub window: Option<(i32, i32)>,
+	pub theme: Option<PathBuf>,
+	pub open: Option<PathBuf>,
+	// Addresses to play or watch over the network through, empty for the default one
+	pub race: Option<String>,
+	pub coop: Option<String>,
+	pub broadcast: Option<String>,
+	pub spectate: Option<String>,
+	pub log_events: bool,
+	// Frames to measure, when benchmarking rendering
+	pub frame_bench: Option<usize>,
+}
+
+impl Options {
+	// Reads the arguments, without the program name, as described by `USAGE`
+	pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, Box<dyn Error>> {
+		let mut options = Self::default();
+		let mut args = args.into_iter().peekable();
+		while let Some(arg) = args.next() {
+			match arg.as_str() {
+				"generate" => options.command = Command::Generate,
+				"solve" => options.command = Command::Solve(parse_value(&arg, args.next())?),
+				"bench" => options.command = Command::Bench,
+				"analyze" => options.command = Command::Analyze,
+				"race-bot" => options.command = Command::RaceBot(optional_address(&mut args)),
+				"coop-server" => options.command = Command::CoopServer(optional_address(&mut args)),
+				"--help" | "-h" => options.command = Command::Help,
+				"--preset" => options.preset = Some(parse_value(&arg, args.next())?),
+				"--width" => options.width = Some(parse_value(&arg, args.next())?),
+				"--height" => options.height = Some(parse_value(&arg, args.next())?),
+				"--mines" => options.mines = Some(parse_value(&arg, args.next())?),
+				"--first-click" => options.first_click = parse_value(&arg, args.next())?,
+				"--seed" => options.seed = Some(parse_value(&arg, args.next())?),
+				"--games" => options.games = Some(parse_value(&arg, args.next())?),
+				"--window" => {
+					let size: String = parse_value(&arg, args.next())?;
+					let size = size.split_once('x').and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)));
+					options.window = Some(size.ok_or("--window must be in the form WIDTHxHEIGHT")?);
+				}
+				"--theme" => options.theme = Some(parse_value(&arg, args.next())?),
+				"--open" => options.open = Some(parse_value(&arg, args.next())?),
+				"--race" => options.race = Some(optional_address(&mut args)),
+				"--coop" => options.coop = Some(optional_address(&mut args)),
+				"--broadcast" => options.broadcast = Some(optional_address(&mut args)),
+				"--spectate" => options.spectate = Some(optional_address(&mut args)),
+				"--log-events" => options.log_events = true,
+				"--frame-bench" => {
+					let frames = args.next_if(|frames| frames.parse::<usize>().is_ok());
+					options.frame_bench = Some(frames.map_or(Ok(DEFAULT_FRAMES), |frames| parse_value(&arg, Some(frames)))?);
+				}
+				_ => return Err(format!("Unknown argument '{}'", arg).into()),
+			}
+		}
+		Ok(options)
+	}
+
+	// Width, height and mine count, from the preset with any of them given on their own taking its place
+	pub fn board(&self) -> (usize, usize, usize) {
+		let (width, height, mines) = self.preset.unwrap_or(Preset::Expert).board();
+		(
+			self.width.unwrap_or(width),
+			self.height.unwrap_or(height),
+			self.mines.unwrap_or(mines),
+		)
+	}
+
+	// Runs the command, which for playing is left to the window
+	pub fn run_command(&self) -> Result<(), Box<dyn Error>> {
+		match &self.command {
+			Command::Play => Ok(()),
+			Command::Generate => self.generate(),
+			Command::Solve(path) => {
+				let text = std::fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+				let board = GameBoard::from_text(&text)?;
+				solve(board);
+				Ok(())
+			}
+			Command::Bench => {
+				let seed = self.seed.unwrap_or_default();
+				let games = self.games.unwrap_or(DEFAULT_GAMES);
+				println!(
+					"{}",
+					runner::run(&mut SimpleAgent::new(seed), self.board(), self.first_click, games, seed)?
+				);
+				Ok(())
+			}
+			Command::Analyze => {
+				let boards = self.games.unwrap_or(DEFAULT_BOARDS);
+				println!(
+					"{}",
+					analysis::analyze(self.board(), self.first_click, boards, self.seed.unwrap_or_default())?
+				);
+				Ok(())
+			}
+			Command::RaceBot(address) => race::run_headless(address),
+			Command::CoopServer(address) => coop_server::run_server(address, self.board()),
+			Command::Help => {
+				println!("{}", USAGE);
+				Ok(())
+			}
+		}
+	}
+
+	fn generate(&self) -> Result<(), Box<dyn Error>> {
+		let (width, height, mines) = self.board();
+		let seed = self.seed.unwrap_or_else(time_seed);
+		let mut game = Minesweeper::new(width, height, mines, self.first_click)?;
+		game.board.seed = Some(seed);
+		game.reveal(width / 2, height / 2);
+		println!("# Seed {}, first click at {}, {}", seed, width / 2, height / 2);
+		print!("{}", game.board.to_text());
+		Ok(())
+	}
+}
+
+// The value following an option
+fn parse_value<T: FromStr>(option: &str, value: Option<String>) -> Result<T, Box<dyn Error>>
+where
+	T::Err: fmt::Display,
+{
+	let value = value.ok_or(format!("{} needs a value", option))?;
+	value.parse().map_err(|error| format!("Bad value '{}' for {}: {}", value, option, error).into())
+}
+
+// The address following an option, if there is one, else an empty one for the default address
+fn optional_address<I: Iterator<Item = String>>(args: &mut Peekable<I>) -> String {
+	args.next_if(|arg| !arg.starts_with('-') && arg.contains(':')).unwrap_or_default()
+}
+
+// Starts on the board's first opening, so no luck is needed to begin with
+fn solve(board: GameBoard) {
+	let Some(&(x, y)) = board.openings().first() else {
+		println!("Needs a guess to start, as every safe tile is next to a mine");
+		return;
+	};
+	let safe_tiles = board.width() * board.height() - board.mines();
+	let mut game = Minesweeper::default();
+	game.load_board(board);
+	game.reveal(x, y);
+	if solver::play_safe(&mut game) {
+		println!("Solved without guessing, starting at {}, {}", x, y);
+	} else {
+		println!(
+			"Needs a guess after revealing {} of {} safe tiles, starting at {}, {}, between {} tiles next to numbers",
+			game.board.revealed_tiles,
+			safe_tiles,
+			x,
+			y,
+			solver::frontier(&VisibleBoard::from(&game.board)).len()
+		);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(args: &str) -> Result<Options, String> {
+		Options::parse(args.split_whitespace().map(String::from)).map_err(|error| error.to_string())
+	}
+
+	#[test]
+	fn commands_are_parsed() {
+		let commands = [
+			("", Command::Play),
+			("generate", Command::Generate),
+			("solve board.txt", Command::Solve("board.txt".into())),
+			("bench", Command::Bench),
+			("analyze", Command::Analyze),
+			("race-bot", Command::RaceBot(String::new())),
+			("race-bot 10.0.0.2:9000", Command::RaceBot("10.0.0.2:9000".into())),
+			("coop-server", Command::CoopServer(String::new())),
+			("coop-server 0.0.0.0:7878", Command::CoopServer("0.0.0.0:7878".into())),
+			("--help", Command::Help),
+			("-h", Command::Help),
+		];
+		for (args, command) in commands {
+			assert_eq!(parse(args).unwrap().command, command, "{}", args);
+		}
+	}
+
+	#[test]
+	fn options_are_parsed() {
+		let options = parse("bench --preset beginner --mines 12 --first-click 5x5 --seed 4 --games 20").unwrap();
+		assert_eq!(options.board(), (9, 9, 12));
+		assert_eq!(options.first_click, FirstClick::Area);
+		assert_eq!((options.seed, options.games), (Some(4), Some(20)));
+
+		let options = parse("--width 20 --height 10 --window 800x600 --open board.txt --log-events").unwrap();
+		assert_eq!(options.board(), (20, 10, 99));
+		assert_eq!(options.window, Some((800, 600)));
+		assert_eq!(options.open, Some("board.txt".into()));
+		assert!(options.log_events);
+	}
+
+	#[test]
+	fn addresses_are_optional() {
+		let options = parse("--race --coop 10.0.0.2:9000 --broadcast --spectate example.com:80").unwrap();
+		assert_eq!(options.race.as_deref(), Some(""));
+		assert_eq!(options.coop.as_deref(), Some("10.0.0.2:9000"));
+		assert_eq!(options.broadcast.as_deref(), Some(""));
+		assert_eq!(options.spectate.as_deref(), Some("example.com:80"));
+		// Whatever follows isn't taken for an address unless it looks like one
+		assert_eq!(parse("race-bot --seed 3").unwrap().command, Command::RaceBot(String::new()));
+		assert_eq!(parse("race-bot bench").unwrap().command, Command::Bench);
+	}
+
+	#[test]
+	fn frame_counts_are_optional() {
+		assert_eq!(parse("--frame-bench").unwrap().frame_bench, Some(DEFAULT_FRAMES));
+		assert_eq!(parse("--frame-bench 30").unwrap().frame_bench, Some(30));
+		assert_eq!(parse("--frame-bench --log-events").unwrap().frame_bench, Some(DEFAULT_FRAMES));
+	}
+
+	#[test]
+	fn bad_arguments_are_refused() {
+		let bad = [
+			("--colour red", "Unknown argument '--colour'"),
+			("play", "Unknown argument 'play'"),
+			("solve", "solve needs a value"),
+			("--width", "--width needs a value"),
+			("--seed", "--seed needs a value"),
+			("--window 800", "--window must be in the form WIDTHxHEIGHT"),
+			("--window 800xtall", "--window must be in the form WIDTHxHEIGHT"),
+		];
+		for (args, error) in bad {
+			assert_eq!(parse(args).err().as_deref(), Some(error), "{}", args);
+		}
+		assert!(parse("--mines many").unwrap_err().starts_with("Bad value 'many' for --mines"));
+		assert!(parse("--preset huge").unwrap_err().starts_with("Bad value 'huge' for --preset"));
+		assert!(parse("--first-click everywhere").is_err());
+	}
+}
@@ -1,39 +1,83 @@
+mod animation;
+mod autoplay;
 mod board_render;
+pub mod camera;
+pub mod coop_view;
+pub mod endless_view;
+pub mod frame_bench;
 mod highlighter;
+pub mod localization;
+mod minimap;
+pub mod race_view;
 pub mod settings_menu;
 mod seven_segment;
+pub mod sound;
 pub mod texture_store;
+pub mod theme;
 mod tile_render;
 pub mod top_menu;
 
-use crate::{logic::game_board::ModifyMode, util::Events};
+use std::path::PathBuf;
+
+use crate::{
+	logic::{
+		endless::EndlessEvent,
+		first_click::FirstClick,
+		game_board::{FlagRules, ModifyMode},
+		hot_seat::MineRule,
+		GameEvent,
+	},
+	util::{Events, Subscriber},
+};
 
 use self::{
+	animation::Animator,
+	autoplay::Autoplay,
+	camera::Camera,
+	endless_view::EndlessView,
 	highlighter::Highlighter,
+	localization::Localization,
+	minimap::Minimap,
 	settings_menu::SettingsMenu,
 	texture_store::TextureStore,
 	top_menu::{smile::SmileyState, GUITop},
 };
 use macroquad::prelude::*;
-#[derive(Default, Copy, Clone, Debug)]
-pub enum Language {
-	#[default]
-	English,
-	Japanese,
-}
-
 pub enum GUIEvent {
 	ClickReset,
 	OpenSettings,
 	CloseSettings,
-	SwitchLanguage(Language),
+	SwitchLanguage(usize),
+	SwitchTheme(Option<PathBuf>),
 	ClickTile(usize, usize),
 	ModifyTile(usize, usize),
+	// The question key, which only puts on question marks when they have a key of their own
+	QuestionTile(usize, usize),
+	ChordTile(usize, usize),
 	HighlightTile(usize, usize),
 	UnHighlightTile(usize, usize),
 	CreateNewGame(usize, usize, usize),
-	SetQuestionMode(ModifyMode),
+	LoadBoard(String),
+	SaveBoard(String),
+	LoadReplay(String),
+	SaveReplay(String),
+	SaveImage(String),
+	SetModifyMode(ModifyMode),
+	SetFlagRules(FlagRules),
+	SetAnimations(bool),
+	SetVolume(f32),
+	// Moves per second the solver plays on its own, where none turns it off
+	SetAutoplay(usize),
 	SetSmileyState(SmileyState),
+	// Number of players taking turns, where one is a normal game, and what happens to those who hit a mine
+	SetHotSeat(usize, MineRule),
+	// What keeps the first reveal safe, which starts a new game
+	SetFirstClick(FirstClick),
+	StartEndless,
+	// Tiles of the endless field, which has no edges so coordinates may be negative
+	EndlessReveal(i64, i64),
+	EndlessModify(i64, i64),
+	EndlessChord(i64, i64),
 }
 
 #[derive(Debug, Default)]
@@ -46,10 +90,12 @@ pub struct UIState {
 	pub reveal_all: bool,
 	pub letterbox: (f32, f32),
 	pub scale: f32,
+	// Physical pixels per logical pixel, above 1 on high-DPI screens
+	pub dpi_scale: f32,
 	pub frozen: bool,
 	pub cursor: (usize, usize),
 	pub settings_open: bool,
-	pub language: Language,
+	pub camera: Camera,
 }
 impl UIState {
 	pub fn new(width: usize, height: usize, tile_size: usize, top_offset: usize) -> Self {
@@ -64,6 +110,7 @@ impl UIState {
 	pub fn update_dimensions(&mut self, width: usize, height: usize) {
 		self.width = width;
 		self.height = height;
+		self.camera.reset();
 	}
 	pub fn update_letterbox(&mut self, screen_width: f32, screen_height: f32) {
 		let game_aspect_ratio = self.width as f32 / (self.height as f32 + self.top_offset as f32 / self.tile_size as f32);
@@ -98,18 +145,70 @@ impl UIState {
 		let y = y as f32;
 		(x * self.scale, y * self.scale)
 	}
+	// Size of the board in unscaled pixels
+	pub fn board_pixel_size(&self) -> (f32, f32) {
+		((self.width * self.tile_size) as f32, (self.height * self.tile_size) as f32)
+	}
+	// Where on screen the board is drawn. The camera picks which part of the board fills it.
+	pub fn board_viewport(&self) -> Rect {
+		let (x, y) = self.pixel_screen_offset(0, self.top_offset);
+		let (width, height) = self.pixel_screen_scale(self.width * self.tile_size, self.height * self.tile_size);
+		Rect::new(x, y, width, height)
+	}
+	// On screen size of a tile, taking the camera's zoom into account
+	pub fn scaled_tile_size(&self) -> f32 {
+		self.tile_size as f32 * self.scale * self.camera.zoom
+	}
+	// Screen position of the top left corner of the tile at `x`, `y`
+	pub fn tile_screen_position(&self, x: usize, y: usize) -> (f32, f32) {
+		let viewport = self.board_viewport();
+		let pixel_scale = self.scale * self.camera.zoom;
+		let x = ((x * self.tile_size) as f32 - self.camera.offset.0) * pixel_scale;
+		let y = ((y * self.tile_size) as f32 - self.camera.offset.1) * pixel_scale;
+		(viewport.x + x, viewport.y + y)
+	}
+	// Columns and rows of tiles which are at least partly on screen
+	pub fn visible_tiles(&self) -> (std::ops::Range<usize>, std::ops::Range<usize>) {
+		let camera = &self.camera;
+		let tile_size = self.tile_size as f32;
+		let (board_width, board_height) = self.board_pixel_size();
+		let first_x = (camera.offset.0 / tile_size) as usize;
+		let first_y = (camera.offset.1 / tile_size) as usize;
+		let last_x = ((camera.offset.0 + board_width / camera.zoom) / tile_size).ceil() as usize;
+		let last_y = ((camera.offset.1 + board_height / camera.zoom) / tile_size).ceil() as usize;
+		(first_x..last_x.min(self.width), first_y..last_y.min(self.height))
+	}
+	pub fn update_camera(&mut self) {
+		self.camera.update(
+			self.board_pixel_size(),
+			self.board_viewport(),
+			self.scale,
+			self.tile_size as f32,
+		);
+	}
 }
 
 #[derive(Default)]
 pub struct GameUI {
 	pub event_handler: Events<GUIEvent>,
+	pub animator: Animator,
+	pub autoplay: Autoplay,
+	pub endless_view: EndlessView,
 	pub highlighter: Highlighter,
+	pub localization: Localization,
+	pub minimap: Minimap,
 	pub state: UIState,
 	pub settings_menu: SettingsMenu,
 	pub texture_store: TextureStore,
 	pub top_menu: GUITop,
 }
 
+// Macroquad draws in logical pixels; this is how many physical pixels each one covers.
+pub fn screen_dpi_scale() -> f32 {
+	// Safe as long as nothing else holds the context, which is the case between macroquad calls.
+	unsafe { get_internal_gl().quad_context.dpi_scale() }
+}
+
 impl GameUI {
 	pub fn new(settings: UIState) -> Self {
 		let set = settings;
@@ -135,11 +234,15 @@ impl GameUI {
 		self.event_handler.clear();
 	}
 
+	// Takes a position in unscaled pixels, relative to the top left of the top bar.
 	pub fn to_coordinate_system(&self, x: f32, y: f32) -> Option<(usize, usize)> {
 		let y = y - self.state.top_offset as f32;
 		if x < 0.0 || y < 0.0 {
 			return None;
 		}
+		let camera = &self.state.camera;
+		let x = x / camera.zoom + camera.offset.0;
+		let y = y / camera.zoom + camera.offset.1;
 		let y = (y / self.state.tile_size as f32) as usize;
 		let x = (x / self.state.tile_size as f32) as usize;
 		if !self.is_valid_position(x, y) {
@@ -148,3 +251,46 @@ impl GameUI {
 		Some((x, y))
 	}
 }
+
+impl Subscriber<GameEvent> for GameUI {
+	fn notify(&mut self, event: &GameEvent) {
+		self.animator.notify(event);
+		self.autoplay.notify(event);
+		match event {
+			GameEvent::Lose(_, _, _) => {
+				self.state.frozen = true;
+				self.event_handler.add(GUIEvent::SetSmileyState(SmileyState::Dead));
+				self.state.reveal_all = true;
+			}
+			GameEvent::Win => {
+				self.state.frozen = true;
+				self.event_handler.add(GUIEvent::SetSmileyState(SmileyState::Victory));
+				self.state.reveal_all = true;
+			}
+			GameEvent::Reset => {
+				self.clear();
+			}
+			_ => (),
+		}
+	}
+}
+
+impl Subscriber<EndlessEvent> for GameUI {
+	fn notify(&mut self, event: &EndlessEvent) {
+		match event {
+			EndlessEvent::Reveal(x, y, _) | EndlessEvent::Flag(x, y) | EndlessEvent::Chord(x, y) => {
+				self.endless_view.follow(*x, *y, &self.state)
+			}
+			EndlessEvent::Lose(x, y) => {
+				self.endless_view.follow(*x, *y, &self.state);
+				self.state.frozen = true;
+				self.event_handler.add(GUIEvent::SetSmileyState(SmileyState::Dead));
+				self.state.reveal_all = true;
+			}
+			EndlessEvent::Reset => {
+				self.clear();
+				self.endless_view.center_on_origin(&self.state);
+			}
+		}
+	}
+}
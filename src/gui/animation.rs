@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use macroquad::time::get_time;
+
+use crate::{
+	logic::{
+		tile::{Tile, TileModifier},
+		GameEvent,
+	},
+	util::Subscriber,
+};
+
+use super::tile_render::TileIndex;
+
+// Seconds between each ring of a cascade reveal
+const RIPPLE_STEP: f64 = 0.025;
+// Seconds between each ring of mines being shown after a loss
+const MINE_REVEAL_STEP: f64 = 0.06;
+const EXPLOSION_TIME: f64 = 0.4;
+const EXPLOSION_GROWTH: f32 = 0.35;
+
+// Delays when tiles are shown so that reveals play out over time.
+// The game logic has already finished by the time anything is animated; this only changes what is drawn.
+pub struct Animator {
+	enabled: bool,
+	cascade: Option<(usize, usize, f64)>,
+	pending_reveals: HashMap<(usize, usize), f64>,
+	explosion: Option<(usize, usize, f64)>,
+}
+
+impl Default for Animator {
+	fn default() -> Self {
+		Self {
+			enabled: true,
+			cascade: None,
+			pending_reveals: HashMap::new(),
+			explosion: None,
+		}
+	}
+}
+
+fn distance(a: (usize, usize), b: (usize, usize)) -> f64 {
+	let x = a.0 as f64 - b.0 as f64;
+	let y = a.1 as f64 - b.1 as f64;
+	(x * x + y * y).sqrt()
+}
+
+impl Animator {
+	pub fn set_enabled(&mut self, enabled: bool) {
+		self.enabled = enabled;
+		if !enabled {
+			self.clear();
+		}
+	}
+
+	pub fn clear(&mut self) {
+		self.cascade = None;
+		self.pending_reveals.clear();
+		self.explosion = None;
+	}
+
+	// Drops reveals which have finished playing
+	pub fn update(&mut self) {
+		if self.pending_reveals.is_empty() {
+			return;
+		}
+		let now = get_time();
+		self.pending_reveals.retain(|_, time| *time > now);
+	}
+
+	// Which sprite to draw for a tile, accounting for reveals which have not played yet.
+	// `mine` is whether the tile holds a mine, once the game is over and the mines are shown.
+	pub fn tile_index(&self, x: usize, y: usize, tile: Tile, mine: Option<bool>) -> TileIndex {
+		if !self.enabled {
+			return tile.render(mine);
+		}
+		let now = get_time();
+		if let Some(&time) = self.pending_reveals.get(&(x, y)) {
+			if time > now {
+				let mut hidden = tile;
+				hidden.swept = false;
+				return hidden.render(None);
+			}
+		}
+		if let Some((explosion_x, explosion_y, start)) = self.explosion {
+			let shows_after_loss = mine == Some(true) || tile.modifier == Some(TileModifier::Flagged);
+			if shows_after_loss && !tile.swept {
+				let time = start + distance((x, y), (explosion_x, explosion_y)) * MINE_REVEAL_STEP;
+				if time > now {
+					return tile.render(None);
+				}
+			}
+		}
+		tile.render(mine)
+	}
+
+	// How much larger than normal to draw a tile; used to make the exploded mine pop.
+	pub fn tile_scale(&self, x: usize, y: usize) -> f32 {
+		if !self.enabled {
+			return 1.0;
+		}
+		match self.explosion {
+			Some((explosion_x, explosion_y, start)) if (explosion_x, explosion_y) == (x, y) => {
+				let progress = (get_time() - start) / EXPLOSION_TIME;
+				if progress < 1.0 {
+					1.0 + EXPLOSION_GROWTH * (progress * std::f64::consts::PI).sin() as f32
+				} else {
+					1.0
+				}
+			}
+			_ => 1.0,
+		}
+	}
+}
+
+impl Subscriber<GameEvent> for Animator {
+	fn notify(&mut self, event: &GameEvent) {
+		if !self.enabled {
+			return;
+		}
+		match *event {
+			GameEvent::RevealTile(x, y, _) => match self.cascade {
+				// The first tile of a sweep is the one which was clicked, and is shown straight away.
+				None => self.cascade = Some((x, y, get_time())),
+				Some((origin_x, origin_y, start)) => {
+					let time = start + distance((x, y), (origin_x, origin_y)) * RIPPLE_STEP;
+					self.pending_reveals.insert((x, y), time);
+				}
+			},
+			GameEvent::SweepDone => self.cascade = None,
+			GameEvent::Lose(x, y, _) | GameEvent::Detonate(x, y, _) => self.explosion = Some((x, y, get_time())),
+			GameEvent::Reset => self.clear(),
+			_ => (),
+		}
+	}
+}
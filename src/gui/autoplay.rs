@@ -0,0 +1,124 @@
+use macroquad::prelude::*;
+
+use crate::{
+	agent::solver::{self, Deductions},
+	logic::{
+		visible::{VisibleBoard, VisibleTile},
+		GameEvent, GameState, Minesweeper,
+	},
+	util::{Events, Subscriber},
+};
+
+use super::{GUIEvent, UIState};
+
+const FRONTIER_COLOR: Color = Color::new(1.0, 0.85, 0.0, 0.8);
+// Width of the frontier outlines, as a share of the tile size
+const FRONTIER_OUTLINE: f32 = 0.08;
+
+// Plays the moves the solver is sure of, through the same events as clicks, at a set number of moves per second.
+// Once only a guess is left it waits for the player, outlining the tiles the guess is between.
+#[derive(Default)]
+pub struct Autoplay {
+	// Moves per second, with none meaning autoplay is off
+	speed: Option<f64>,
+	last_move: f64,
+	// Moves worked out from the board as it was, which are checked against the board before being made
+	pending: Deductions,
+	// Set while waiting for a guess, until the board changes
+	stuck: bool,
+	frontier: Vec<(usize, usize)>,
+}
+
+impl Autoplay {
+	pub fn set_speed(&mut self, moves_per_second: usize) {
+		self.speed = (moves_per_second > 0).then_some(moves_per_second as f64);
+		self.clear();
+	}
+
+	fn clear(&mut self) {
+		self.pending = Deductions::default();
+		self.stuck = false;
+		self.frontier.clear();
+	}
+
+	// Adds the next move once it's due, which is at most one a frame
+	pub fn update(&mut self, game: &Minesweeper, ui_state: &UIState, event_handler: &mut Events<GUIEvent>) {
+		let Some(speed) = self.speed else {
+			return;
+		};
+		let now = get_time();
+		if ui_state.frozen || self.stuck || now - self.last_move < 1.0 / speed {
+			return;
+		}
+		if game.state == GameState::Empty {
+			// The first reveal is safe unless the first click rule leaves it unprotected
+			event_handler.add(GUIEvent::ClickTile(game.board.width() / 2, game.board.height() / 2));
+			self.last_move = now;
+			return;
+		}
+		let board = VisibleBoard::from(&game.board);
+		if self.pending.is_empty() {
+			self.pending = solver::deduce(&board);
+			self.pending.safe.reverse();
+			self.pending.mines.reverse();
+		}
+		match next_move(&mut self.pending, &board) {
+			Some(event) => {
+				event_handler.add(event);
+				self.last_move = now;
+			}
+			None => {
+				self.stuck = true;
+				self.frontier = solver::frontier(&board);
+			}
+		}
+	}
+
+	pub fn render(&self, ui_state: &UIState) {
+		let tile_size = ui_state.scaled_tile_size();
+		let (xs, ys) = ui_state.visible_tiles();
+		for &(x, y) in self.frontier.iter().filter(|(x, y)| xs.contains(x) && ys.contains(y)) {
+			let (screen_x, screen_y) = ui_state.tile_screen_position(x, y);
+			draw_rectangle_lines(
+				screen_x,
+				screen_y,
+				tile_size,
+				tile_size,
+				tile_size * FRONTIER_OUTLINE,
+				FRONTIER_COLOR,
+			);
+		}
+	}
+}
+
+// Reveals safe tiles before flagging mines, skipping moves the board has caught up with. A flagged safe tile has
+// its flag taken off first, and is revealed once it is worked out again.
+fn next_move(pending: &mut Deductions, board: &VisibleBoard) -> Option<GUIEvent> {
+	while let Some((x, y)) = pending.safe.pop() {
+		match board.get(x, y) {
+			Some(VisibleTile::Hidden | VisibleTile::Questioned) => return Some(GUIEvent::ClickTile(x, y)),
+			Some(VisibleTile::Flagged) => return Some(GUIEvent::ModifyTile(x, y)),
+			_ => (),
+		}
+	}
+	while let Some((x, y)) = pending.mines.pop() {
+		if let Some(VisibleTile::Hidden | VisibleTile::Questioned) = board.get(x, y) {
+			return Some(GUIEvent::ModifyTile(x, y));
+		}
+	}
+	None
+}
+
+impl Subscriber<GameEvent> for Autoplay {
+	fn notify(&mut self, event: &GameEvent) {
+		match event {
+			// Anything the player does may leave something new to work out
+			GameEvent::RevealTile(..) | GameEvent::FlagTile(..) | GameEvent::QuestionTile(..) | GameEvent::Detonate(..) => {
+				self.stuck = false;
+				self.frontier.clear();
+			}
+			GameEvent::Reset | GameEvent::Lose(..) | GameEvent::Win => self.clear(),
+			_ => (),
+		}
+	}
+}
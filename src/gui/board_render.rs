@@ -1,28 +1,41 @@
 use crate::{logic::game_board::GameBoard, util::Events};
 use macroquad::prelude::*;
 
-use super::{texture_store::TextureStore, GUIEvent, UIState};
+use super::{animation::Animator, texture_store::TextureStore, GUIEvent, UIState};
 impl GameBoard {
-	pub fn render(&self, textures: &TextureStore, settings: &UIState) {
+	pub fn render(&self, textures: &TextureStore, settings: &UIState, animator: &Animator) {
 		// dbg!(&settings.top_offset, &settings.render_scale);
-		let tile_size = settings.tile_size;
-		let (scaled_tile, _) = settings.pixel_screen_scale(tile_size, 0);
-		for (x, col) in self.tiles.iter().enumerate() {
-			for (y, tile) in col.iter().enumerate() {
-				let (x, y) = settings.pixel_screen_offset(x * tile_size, y * tile_size + settings.top_offset);
+		let scaled_tile = settings.scaled_tile_size();
+		let tiles = textures.get_tiles(scaled_tile * settings.dpi_scale);
+		let viewport = settings.board_viewport();
+		// When zoomed in, tiles partly outside the viewport are cut off at its edges
+		let dpi = settings.dpi_scale;
+		unsafe { get_internal_gl() }.quad_gl.scissor(Some((
+			(viewport.x * dpi) as i32,
+			(viewport.y * dpi) as i32,
+			(viewport.w * dpi).ceil() as i32,
+			(viewport.h * dpi).ceil() as i32,
+		)));
+		let (columns, rows) = settings.visible_tiles();
+		for x in columns {
+			for y in rows.clone() {
+				let Some(tile) = self.get_tile(x, y) else {
+					continue;
+				};
+				let (screen_x, screen_y) = settings.tile_screen_position(x, y);
+				let tile_index = animator.tile_index(x, y, *tile, self.shown_mine(x, y));
+				let tile_scale = animator.tile_scale(x, y);
+				let (x, y) = (screen_x, screen_y);
+				// Grow from the center of the tile rather than its corner
+				let growth = scaled_tile * (tile_scale - 1.0) * 0.5;
 				draw_texture_ex(
-					textures.get_tiles()[tile.render(settings.reveal_all) as usize],
-					x,
-					y,
+					tiles.texture,
+					x - growth,
+					y - growth,
 					WHITE,
 					DrawTextureParams {
-						dest_size: Some(vec2(scaled_tile, scaled_tile)),
-						source: Some(Rect {
-							x: 0.0,
-							y: 0.0,
-							w: 32.0,
-							h: 32.0,
-						}),
+						dest_size: Some(vec2(scaled_tile * tile_scale, scaled_tile * tile_scale)),
+						source: Some(tiles.source(tile_index as usize)),
 						rotation: 0.0,
 						flip_x: false,
 						flip_y: false,
@@ -31,6 +44,7 @@ impl GameBoard {
 				);
 			}
 		}
+		unsafe { get_internal_gl() }.quad_gl.scissor(None);
 	}
 	pub fn events(&self, settings: &UIState, event_handler: &mut Events<GUIEvent>) {
 		if settings.mouse_in_minefield && !settings.frozen {
@@ -40,6 +54,9 @@ impl GameBoard {
 			if is_mouse_button_released(MouseButton::Right) {
 				event_handler.add(GUIEvent::ModifyTile(settings.cursor.0, settings.cursor.1))
 			}
+			if is_key_pressed(KeyCode::Q) {
+				event_handler.add(GUIEvent::QuestionTile(settings.cursor.0, settings.cursor.1))
+			}
 		}
 	}
 }
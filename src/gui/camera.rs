@@ -0,0 +1,152 @@
+use macroquad::prelude::*;
+
+// Zoom limits, as the on screen size of a tile in logical pixels. On huge boards the camera
+// stays zoomed in far enough that tiles are still worth drawing.
+const MIN_TILE_SIZE: f32 = 6.0;
+const MAX_TILE_SIZE: f32 = 128.0;
+// How much one notch of the mouse wheel zooms by
+const WHEEL_ZOOM_STEP: f32 = 1.15;
+// Logical pixels per second, for keyboard and edge panning
+const PAN_SPEED: f32 = 600.0;
+// Panning starts when the mouse is this many logical pixels from the edge of the board
+const EDGE_PAN_MARGIN: f32 = 12.0;
+
+// Which part of the board is on screen. At a zoom of 1 the whole board fits the window,
+// higher zooms show part of it at a larger size.
+#[derive(Debug, Clone, Copy)]
+pub struct Camera {
+	pub zoom: f32,
+	min_zoom: f32,
+	max_zoom: f32,
+	// Top left of the view, in unscaled board pixels
+	pub offset: (f32, f32),
+	// Mouse position and offset when a drag began
+	drag_start: Option<(Vec2, (f32, f32))>,
+}
+
+impl Default for Camera {
+	fn default() -> Self {
+		Self {
+			zoom: 1.0,
+			min_zoom: 1.0,
+			max_zoom: 1.0,
+			offset: (0.0, 0.0),
+			drag_start: None,
+		}
+	}
+}
+
+impl Camera {
+	pub fn reset(&mut self) {
+		*self = Self::default();
+	}
+
+	// Whether only part of the board is on screen
+	pub fn is_zoomed(&self) -> bool {
+		self.zoom > 1.0
+	}
+
+	// True while space is held, when the left mouse button drags the view instead of clicking tiles.
+	pub fn is_panning(&self) -> bool {
+		is_key_down(KeyCode::Space) || self.drag_start.is_some()
+	}
+
+	// `board_size` is in unscaled board pixels, `viewport` is where the board is drawn on screen,
+	// `scale` converts unscaled pixels to screen pixels at a zoom of 1 and `tile_size` is in unscaled pixels.
+	pub fn update(&mut self, board_size: (f32, f32), viewport: Rect, scale: f32, tile_size: f32) {
+		let fitted_tile_size = tile_size * scale;
+		self.min_zoom = (MIN_TILE_SIZE / fitted_tile_size).max(1.0);
+		self.max_zoom = (MAX_TILE_SIZE / fitted_tile_size).max(self.min_zoom);
+		if self.zoom < self.min_zoom {
+			self.zoom = self.min_zoom;
+			self.center_on(board_size.0 * 0.5, board_size.1 * 0.5, board_size);
+		}
+
+		let mouse = Vec2::from(mouse_position());
+		let mouse_in_viewport = viewport.contains(mouse);
+		let pixel_scale = scale * self.zoom;
+
+		let (_, wheel) = mouse_wheel();
+		if mouse_in_viewport && wheel != 0.0 {
+			self.zoom_at(WHEEL_ZOOM_STEP.powf(wheel.signum()), mouse - viewport.point(), scale);
+		}
+		if is_key_pressed(KeyCode::Equal) || is_key_pressed(KeyCode::KpAdd) {
+			self.zoom_at(WHEEL_ZOOM_STEP * WHEEL_ZOOM_STEP, viewport.size() * 0.5, scale);
+		}
+		if is_key_pressed(KeyCode::Minus) || is_key_pressed(KeyCode::KpSubtract) {
+			self.zoom_at(1.0 / (WHEEL_ZOOM_STEP * WHEEL_ZOOM_STEP), viewport.size() * 0.5, scale);
+		}
+		if is_key_pressed(KeyCode::Key0) {
+			self.reset();
+		}
+
+		match self.drag_start {
+			Some((start, offset)) if is_mouse_button_down(MouseButton::Left) => {
+				let moved = (mouse - start) / pixel_scale;
+				self.offset = (offset.0 - moved.x, offset.1 - moved.y);
+			}
+			Some(_) => self.drag_start = None,
+			None => {
+				if is_key_down(KeyCode::Space) && mouse_in_viewport && is_mouse_button_pressed(MouseButton::Left) {
+					self.drag_start = Some((mouse, self.offset));
+				}
+			}
+		}
+
+		let mut pan = Vec2::ZERO;
+		if is_key_down(KeyCode::Left) {
+			pan.x -= 1.0;
+		}
+		if is_key_down(KeyCode::Right) {
+			pan.x += 1.0;
+		}
+		if is_key_down(KeyCode::Up) {
+			pan.y -= 1.0;
+		}
+		if is_key_down(KeyCode::Down) {
+			pan.y += 1.0;
+		}
+		if self.is_zoomed() && mouse_in_viewport && self.drag_start.is_none() {
+			if mouse.x < viewport.left() + EDGE_PAN_MARGIN {
+				pan.x -= 1.0;
+			}
+			if mouse.x > viewport.right() - EDGE_PAN_MARGIN {
+				pan.x += 1.0;
+			}
+			if mouse.y < viewport.top() + EDGE_PAN_MARGIN {
+				pan.y -= 1.0;
+			}
+			if mouse.y > viewport.bottom() - EDGE_PAN_MARGIN {
+				pan.y += 1.0;
+			}
+		}
+		let pan = pan.clamp(Vec2::splat(-1.0), Vec2::ONE) * PAN_SPEED * get_frame_time() / pixel_scale;
+		self.offset.0 += pan.x;
+		self.offset.1 += pan.y;
+
+		self.clamp(board_size);
+	}
+
+	// Centers the view on a point of the board, given in unscaled board pixels.
+	pub fn center_on(&mut self, x: f32, y: f32, board_size: (f32, f32)) {
+		let (view_width, view_height) = (board_size.0 / self.zoom, board_size.1 / self.zoom);
+		self.offset = (x - view_width * 0.5, y - view_height * 0.5);
+		self.clamp(board_size);
+	}
+
+	// Zooms by `factor`, keeping the board under `anchor` (relative to the viewport, in screen pixels) in place.
+	fn zoom_at(&mut self, factor: f32, anchor: Vec2, scale: f32) {
+		let old_zoom = self.zoom;
+		self.zoom = (self.zoom * factor).clamp(self.min_zoom, self.max_zoom);
+		let anchor = anchor / scale;
+		self.offset.0 += anchor.x / old_zoom - anchor.x / self.zoom;
+		self.offset.1 += anchor.y / old_zoom - anchor.y / self.zoom;
+	}
+
+	// Keeps the view inside the board
+	fn clamp(&mut self, board_size: (f32, f32)) {
+		let max_x = board_size.0 - board_size.0 / self.zoom;
+		let max_y = board_size.1 - board_size.1 / self.zoom;
+		self.offset = (self.offset.0.clamp(0.0, max_x), self.offset.1.clamp(0.0, max_y));
+	}
+}
@@ -0,0 +1,41 @@
+use macroquad::prelude::*;
+
+use crate::net::coop::CoopClient;
+
+use super::{top_menu::player_scores::PLAYER_COLORS, UIState};
+
+// Widths of the outlines, as a share of the tile size
+const FLAG_OUTLINE: f32 = 0.08;
+const CURSOR_OUTLINE: f32 = 0.15;
+
+fn player_color(player: usize) -> Color {
+	PLAYER_COLORS[player % PLAYER_COLORS.len()]
+}
+
+// Outlines every flag in the colour of the player who placed it, and every other player's cursor in theirs.
+// Spectators only see the cursor, as there is just the one player.
+pub fn render(coop: &CoopClient, ui_state: &UIState) {
+	let tile_size = ui_state.scaled_tile_size();
+	let (xs, ys) = ui_state.visible_tiles();
+	let visible = |x: usize, y: usize| xs.contains(&x) && ys.contains(&y);
+	let flag_owners = coop.flag_owners.iter().filter(|_| !coop.read_only);
+	for (&(x, y), &player) in flag_owners.filter(|((x, y), _)| visible(*x, *y)) {
+		let (screen_x, screen_y) = ui_state.tile_screen_position(x, y);
+		let color = Color {
+			a: 0.7,
+			..player_color(player)
+		};
+		draw_rectangle_lines(screen_x, screen_y, tile_size, tile_size, tile_size * FLAG_OUTLINE, color);
+	}
+	for (&player, &(x, y)) in coop.cursors.iter().filter(|(_, (x, y))| visible(*x, *y)) {
+		let (screen_x, screen_y) = ui_state.tile_screen_position(x, y);
+		draw_rectangle_lines(
+			screen_x,
+			screen_y,
+			tile_size,
+			tile_size,
+			tile_size * CURSOR_OUTLINE,
+			player_color(player),
+		);
+	}
+}
@@ -0,0 +1,153 @@
+use macroquad::prelude::*;
+
+use crate::{logic::endless::EndlessGame, util::Events};
+
+use super::{texture_store::TextureStore, GUIEvent, UIState};
+
+// Tiles across and down the view
+pub const ENDLESS_VIEW_SIZE: (usize, usize) = (30, 16);
+// Tiles per second, for keyboard panning
+const PAN_SPEED: f64 = 20.0;
+// The camera moves to keep reveals at least this many tiles from the edge of the view
+const FOLLOW_MARGIN: f64 = 4.0;
+// How quickly the camera catches up with where it is following, per second
+const FOLLOW_SPEED: f64 = 6.0;
+
+// Shows part of an endless field, using the window's board area as a view of `UIState::width` by
+// `UIState::height` tiles. The camera follows the player as they reveal tiles.
+#[derive(Default)]
+pub struct EndlessView {
+	// Top left of the view, in tiles
+	offset: (f64, f64),
+	// Where the camera is moving to
+	target: (f64, f64),
+	// Mouse position and offset when a drag began
+	drag_start: Option<(Vec2, (f64, f64))>,
+}
+
+impl EndlessView {
+	// Puts the origin, where the field is safe to start, in the middle of the view.
+	pub fn center_on_origin(&mut self, ui_state: &UIState) {
+		self.offset = (-(ui_state.width as f64) / 2.0, -(ui_state.height as f64) / 2.0);
+		self.target = self.offset;
+	}
+
+	// Moves the camera if `x`, `y` is close to or past the edge of the view.
+	pub fn follow(&mut self, x: i64, y: i64, ui_state: &UIState) {
+		let (width, height) = (ui_state.width as f64, ui_state.height as f64);
+		let (x, y) = (x as f64 + 0.5, y as f64 + 0.5);
+		let keep_in_view = |position: f64, offset: f64, size: f64| {
+			let margin = FOLLOW_MARGIN.min(size / 2.0);
+			if position < offset + margin {
+				position - margin
+			} else if position > offset + size - margin {
+				position + margin - size
+			} else {
+				offset
+			}
+		};
+		self.target = (keep_in_view(x, self.target.0, width), keep_in_view(y, self.target.1, height));
+	}
+
+	// The tile under the mouse, if the mouse is over the board area
+	fn hovered_tile(&self, ui_state: &UIState) -> Option<(i64, i64)> {
+		let viewport = ui_state.board_viewport();
+		let mouse = Vec2::from(mouse_position());
+		if !viewport.contains(mouse) {
+			return None;
+		}
+		let tile_size = (ui_state.tile_size as f32 * ui_state.scale) as f64;
+		let x = self.offset.0 + (mouse.x - viewport.x) as f64 / tile_size;
+		let y = self.offset.1 + (mouse.y - viewport.y) as f64 / tile_size;
+		Some((x.floor() as i64, y.floor() as i64))
+	}
+
+	pub fn update(&mut self, game: &EndlessGame, ui_state: &UIState, event_handler: &mut Events<GUIEvent>) {
+		let frame_time = get_frame_time() as f64;
+		let mouse = Vec2::from(mouse_position());
+		let tile_size = (ui_state.tile_size as f32 * ui_state.scale) as f64;
+
+		// Panning by hand takes over from following
+		let mut pan = (0.0, 0.0);
+		if is_key_down(KeyCode::Left) {
+			pan.0 -= 1.0;
+		}
+		if is_key_down(KeyCode::Right) {
+			pan.0 += 1.0;
+		}
+		if is_key_down(KeyCode::Up) {
+			pan.1 -= 1.0;
+		}
+		if is_key_down(KeyCode::Down) {
+			pan.1 += 1.0;
+		}
+		self.target.0 += pan.0 * PAN_SPEED * frame_time;
+		self.target.1 += pan.1 * PAN_SPEED * frame_time;
+		match self.drag_start {
+			Some((start, offset)) if is_mouse_button_down(MouseButton::Left) => {
+				let moved = mouse - start;
+				self.target = (offset.0 - moved.x as f64 / tile_size, offset.1 - moved.y as f64 / tile_size);
+				self.offset = self.target;
+			}
+			Some(_) => self.drag_start = None,
+			None => {
+				if is_key_down(KeyCode::Space) && is_mouse_button_pressed(MouseButton::Left) {
+					self.drag_start = Some((mouse, self.offset));
+				}
+			}
+		}
+		let catch_up = (FOLLOW_SPEED * frame_time).min(1.0);
+		self.offset.0 += (self.target.0 - self.offset.0) * catch_up;
+		self.offset.1 += (self.target.1 - self.offset.1) * catch_up;
+
+		if ui_state.frozen || self.drag_start.is_some() || is_key_down(KeyCode::Space) {
+			return;
+		}
+		let Some((x, y)) = self.hovered_tile(ui_state) else {
+			return;
+		};
+		if is_mouse_button_released(MouseButton::Left) {
+			event_handler.add(GUIEvent::EndlessReveal(x, y));
+		}
+		if is_mouse_button_released(MouseButton::Right) {
+			event_handler.add(GUIEvent::EndlessModify(x, y));
+		}
+		if is_mouse_button_pressed(MouseButton::Middle) && !game.chord_targets(x, y).is_empty() {
+			event_handler.add(GUIEvent::EndlessChord(x, y));
+		}
+	}
+
+	pub fn render(&self, game: &EndlessGame, textures: &TextureStore, ui_state: &UIState) {
+		let viewport = ui_state.board_viewport();
+		let tile_size = ui_state.tile_size as f32 * ui_state.scale;
+		let tiles = textures.get_tiles(tile_size * ui_state.dpi_scale);
+		let dpi = ui_state.dpi_scale;
+		unsafe { get_internal_gl() }.quad_gl.scissor(Some((
+			(viewport.x * dpi) as i32,
+			(viewport.y * dpi) as i32,
+			(viewport.w * dpi).ceil() as i32,
+			(viewport.h * dpi).ceil() as i32,
+		)));
+		let first_x = self.offset.0.floor() as i64;
+		let first_y = self.offset.1.floor() as i64;
+		for x in first_x..=first_x + ui_state.width as i64 {
+			for y in first_y..=first_y + ui_state.height as i64 {
+				let tile = game.get_tile(x, y);
+				let screen_x = viewport.x + ((x as f64 - self.offset.0) * tile_size as f64) as f32;
+				let screen_y = viewport.y + ((y as f64 - self.offset.1) * tile_size as f64) as f32;
+				draw_texture_ex(
+					tiles.texture,
+					screen_x,
+					screen_y,
+					WHITE,
+					DrawTextureParams {
+						dest_size: Some(vec2(tile_size, tile_size)),
+						source: Some(tiles.source(tile.render(game.shown_mine(x, y)) as usize)),
+						..Default::default()
+					},
+				);
+			}
+		}
+		unsafe { get_internal_gl() }.quad_gl.scissor(None);
+	}
+}
@@ -0,0 +1,73 @@
+use macroquad::time::{get_frame_time, get_time};
+
+// The largest board the settings menu allows, with a mine density close to expert
+pub const BENCH_BOARD: (usize, usize, usize) = (1000, 1000, 200000);
+
+// Measures how long frames take, for checking rendering performance on large boards.
+// Enabled with the --frame-bench option, given the number of frames to measure.
+pub struct FrameBench {
+	frames: usize,
+	frame_start: f64,
+	// Time spent updating and drawing each frame, before it is handed to the GPU
+	work_times: Vec<f64>,
+	// Time between frames, as macroquad measures it
+	frame_times: Vec<f64>,
+}
+
+impl FrameBench {
+	pub fn new(frames: usize) -> Self {
+		Self {
+			frames: frames.max(1),
+			frame_start: 0.0,
+			work_times: vec![],
+			frame_times: vec![],
+		}
+	}
+
+	pub fn begin_frame(&mut self) {
+		self.frame_start = get_time();
+	}
+
+	pub fn end_frame(&mut self) {
+		self.work_times.push(get_time() - self.frame_start);
+		// The first frame time covers startup rather than a frame
+		if self.work_times.len() > 1 {
+			self.frame_times.push(get_frame_time() as f64);
+		}
+	}
+
+	pub fn is_finished(&self) -> bool {
+		self.work_times.len() >= self.frames
+	}
+
+	pub fn report(&self) -> String {
+		let (width, height, mines) = BENCH_BOARD;
+		format!(
+			"{} frames on a {}x{} board with {} mines\nupdate and draw: {}\nframe:           {}",
+			self.work_times.len(),
+			width,
+			height,
+			mines,
+			summarize(&self.work_times),
+			summarize(&self.frame_times),
+		)
+	}
+}
+
+// Mean, median, 99th percentile and maximum, in milliseconds
+fn summarize(times: &[f64]) -> String {
+	if times.is_empty() {
+		return String::from("no frames");
+	}
+	let mut sorted = times.to_vec();
+	sorted.sort_by(f64::total_cmp);
+	let percentile = |p: f64| sorted[((sorted.len() - 1) as f64 * p).round() as usize] * 1000.0;
+	let mean = sorted.iter().sum::<f64>() / sorted.len() as f64 * 1000.0;
+	format!(
+		"mean {:.3}ms, median {:.3}ms, p99 {:.3}ms, max {:.3}ms",
+		mean,
+		percentile(0.5),
+		percentile(0.99),
+		percentile(1.0)
+	)
+}
@@ -16,9 +16,11 @@ use std::default;
 #[derive(Default)]
 pub struct Highlighter {
 	cursor_old: Option<(usize, usize)>,
+	// The highlight which was last sent out, so nothing is sent while it stays the same
+	highlight_old: Highlight,
 	pub highlight: Highlight,
 }
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Default, PartialEq)]
 pub enum Highlight {
 	#[default]
 	None,
@@ -52,40 +54,8 @@ impl Highlighter {
 
 	fn check_reveal(&self, event_handler: &mut Events<GUIEvent>, interface: &UIState, game_board: &mut GameBoard) {
 		let (x, y) = interface.cursor;
-		if let Some(tile) = game_board.get_tile_mut(x, y) {
-			let adjacent_mines = tile.adjacent;
-			if !tile.swept {
-				return;
-			}
-			if adjacent_mines == 0 {
-				return;
-			}
-			let mut adjacent_flags = 0;
-			let mut near = ADJACENT_WITHOUT_CENTER.to_vec();
-			near.retain_mut(|pos| {
-				pos.0 += x as isize;
-				pos.1 += y as isize;
-				if pos.0 < 0 || pos.1 < 0 {
-					return false;
-				}
-				let x = pos.0 as usize;
-				let y = pos.1 as usize;
-				if let Some(tile) = game_board.get_tile(x, y) {
-					if let Some(TileModifier::Flagged) = tile.modifier {
-						adjacent_flags += 1;
-						return false;
-					}
-					return true;
-				}
-				false
-			});
-			if adjacent_flags == adjacent_mines {
-				for empty_tile in near.iter() {
-					let x = empty_tile.0 as usize;
-					let y = empty_tile.1 as usize;
-					event_handler.add(GUIEvent::ClickTile(x, y));
-				}
-			}
+		if !game_board.chord_targets(x, y).is_empty() {
+			event_handler.add(GUIEvent::ChordTile(x, y));
 		}
 	}
 	pub fn highlight(&mut self, interface: &UIState, event_handler: &mut Events<GUIEvent>) {
@@ -93,6 +63,14 @@ impl Highlighter {
 			return;
 		}
 		let (x, y) = interface.cursor;
+		if self.cursor_old == Some((x, y)) && self.highlight_old == self.highlight {
+			return;
+		}
+		if self.highlight_old != self.highlight {
+			// Going from a normal to a wide highlight, nothing needs to be taken away
+			self.cursor_old = None;
+		}
+		self.highlight_old = self.highlight;
 		match self.highlight {
 			Highlight::None => {}
 			Highlight::Normal => {
@@ -190,6 +168,7 @@ impl Highlighter {
 		}
 
 		self.highlight = Highlight::None;
+		self.highlight_old = Highlight::None;
 		self.cursor_old = None;
 	}
 }
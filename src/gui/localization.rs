@@ -0,0 +1,125 @@
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
+use crate::util::parse_key_values;
+
+use super::texture_store::JAPANESE_TILE_SHEETS;
+
+pub const LANGUAGE_DIRECTORY: &str = "languages";
+const FALLBACK_LANGUAGE: usize = 0;
+
+// A message catalog, plus the optional tile sheets and font the language is drawn with.
+//
+// Catalogs are `key = value` files. Besides messages, they may set:
+//
+//     language.name = Deutsch
+//     language.tiles = tiles_16x.png, tiles_32x.png
+//     language.font = font.ttf
+//
+// where files are relative to the catalog. `language.tiles` may list the same sheet at several resolutions. Any message a catalog leaves out is shown in English.
+pub struct Language {
+	pub name: String,
+	pub tile_sheets: Vec<Vec<u8>>,
+	pub font: Option<Vec<u8>>,
+	messages: HashMap<String, String>,
+}
+
+impl Language {
+	fn from_catalog(text: &str, tile_sheets: Vec<Vec<u8>>) -> Result<Self, Box<dyn Error>> {
+		let mut messages = parse_key_values(text)?;
+		Ok(Self {
+			name: messages.remove("language.name").ok_or("Catalog is missing `language.name`")?,
+			tile_sheets,
+			font: None,
+			messages,
+		})
+	}
+
+	pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+		let text = fs::read_to_string(path)?;
+		let mut language = Self::from_catalog(&text, vec![])?;
+		let directory = path.parent().unwrap_or(Path::new(""));
+		if let Some(tiles) = language.messages.remove("language.tiles") {
+			for tiles in tiles.split(',').map(str::trim) {
+				let tiles_path = directory.join(tiles);
+				language
+					.tile_sheets
+					.push(fs::read(&tiles_path).map_err(|error| format!("{}: {}", tiles_path.display(), error))?);
+			}
+		}
+		if let Some(font) = language.messages.remove("language.font") {
+			let font_path = directory.join(font);
+			language.font = Some(fs::read(&font_path).map_err(|error| format!("{}: {}", font_path.display(), error))?);
+		}
+		Ok(language)
+	}
+}
+
+pub struct Localization {
+	languages: Vec<Language>,
+	current: usize,
+}
+
+impl Default for Localization {
+	fn default() -> Self {
+		let builtin = [
+			(include_str!("../../assets/lang/en.txt"), vec![]),
+			(
+				include_str!("../../assets/lang/ja.txt"),
+				JAPANESE_TILE_SHEETS.map(<[u8]>::to_vec).to_vec(),
+			),
+		];
+		let languages = builtin
+			.into_iter()
+			.map(|(catalog, tiles)| Language::from_catalog(catalog, tiles).expect("Could not load built in language"))
+			.collect();
+		Self { languages, current: 0 }
+	}
+}
+
+impl Localization {
+	// Adds every `.txt` catalog in `directory` after the built in languages.
+	pub fn load_directory(&mut self, directory: &Path) {
+		let Ok(entries) = fs::read_dir(directory) else {
+			return;
+		};
+		let mut paths: Vec<_> = entries
+			.flatten()
+			.map(|entry| entry.path())
+			.filter(|path| path.extension().is_some_and(|extension| extension == "txt"))
+			.collect();
+		paths.sort();
+		for path in paths {
+			match Language::load(&path) {
+				Ok(language) => self.languages.push(language),
+				Err(error) => eprintln!("Could not load language from {}: {}", path.display(), error),
+			}
+		}
+	}
+
+	// The message for `key` in the current language, falling back to English and then to the key itself.
+	pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+		self.languages[self.current]
+			.messages
+			.get(key)
+			.or_else(|| self.languages[FALLBACK_LANGUAGE].messages.get(key))
+			.map_or(key, String::as_str)
+	}
+
+	pub fn names(&self) -> impl Iterator<Item = &str> {
+		self.languages.iter().map(|language| language.name.as_str())
+	}
+
+	pub fn current(&self) -> &Language {
+		&self.languages[self.current]
+	}
+
+	pub fn current_index(&self) -> usize {
+		self.current
+	}
+
+	pub fn set_language(&mut self, index: usize) {
+		if index < self.languages.len() {
+			self.current = index;
+		}
+	}
+}
@@ -0,0 +1,134 @@
+use macroquad::prelude::*;
+
+use crate::logic::{game_board::GameBoard, visible::VisibleTile};
+
+use super::UIState;
+
+// Length of the minimap's longest side, in screen pixels
+const MINIMAP_SIZE: f32 = 150.0;
+const MINIMAP_MARGIN: f32 = 10.0;
+
+// Overview of the whole board, shown in the corner of the board while zoomed in.
+// Clicking or dragging on it moves the camera.
+#[derive(Default)]
+pub struct Minimap {
+	image: Option<Image>,
+	texture: Option<Texture2D>,
+	// Revealed tiles, remaining flags and whether mines were shown when the texture was last drawn.
+	// Tiles only change when one of these does, so the texture is only redrawn then.
+	drawn: Option<(usize, isize, bool)>,
+}
+
+impl Minimap {
+	// Where the minimap is drawn, or None when the whole board is already visible
+	pub fn rect(ui_state: &UIState) -> Option<Rect> {
+		if !ui_state.camera.is_zoomed() {
+			return None;
+		}
+		let viewport = ui_state.board_viewport();
+		let scale = MINIMAP_SIZE / ui_state.width.max(ui_state.height) as f32;
+		let (width, height) = (ui_state.width as f32 * scale, ui_state.height as f32 * scale);
+		Some(Rect::new(
+			viewport.right() - width - MINIMAP_MARGIN,
+			viewport.bottom() - height - MINIMAP_MARGIN,
+			width,
+			height,
+		))
+	}
+
+	// Moves the camera when the minimap is clicked. Returns whether the mouse is over the minimap,
+	// in which case the board underneath should not react to it.
+	pub fn events(&self, ui_state: &mut UIState) -> bool {
+		let Some(rect) = Self::rect(ui_state) else {
+			return false;
+		};
+		let mouse = Vec2::from(mouse_position());
+		if !rect.contains(mouse) {
+			return false;
+		}
+		if is_mouse_button_down(MouseButton::Left) {
+			let (board_width, board_height) = ui_state.board_pixel_size();
+			let x = (mouse.x - rect.x) / rect.w * board_width;
+			let y = (mouse.y - rect.y) / rect.h * board_height;
+			ui_state.camera.center_on(x, y, (board_width, board_height));
+		}
+		true
+	}
+
+	pub fn render(&mut self, board: &GameBoard, ui_state: &UIState) {
+		let Some(rect) = Self::rect(ui_state) else {
+			return;
+		};
+		let (width, height) = (board.width() as u16, board.height() as u16);
+		// The texture is only recreated when the board changes size
+		if self.image.as_ref().map(|image| (image.width, image.height)) != Some((width, height)) {
+			if let Some(texture) = self.texture.take() {
+				texture.delete();
+			}
+			let image = Image::gen_image_color(width, height, BLACK);
+			let texture = Texture2D::from_image(&image);
+			texture.set_filter(FilterMode::Nearest);
+			self.image = Some(image);
+			self.texture = Some(texture);
+			self.drawn = None;
+		}
+		let (Some(image), Some(texture)) = (self.image.as_mut(), self.texture) else {
+			return;
+		};
+		let state = (board.revealed_tiles, board.remaining_flags(), ui_state.reveal_all);
+		if self.drawn != Some(state) {
+			self.drawn = Some(state);
+			draw_tiles(image, board, ui_state.reveal_all);
+			texture.update(image);
+		}
+
+		draw_rectangle(
+			rect.x - 2.0,
+			rect.y - 2.0,
+			rect.w + 4.0,
+			rect.h + 4.0,
+			Color::from_rgba(40, 40, 40, 220),
+		);
+		draw_texture_ex(
+			texture,
+			rect.x,
+			rect.y,
+			WHITE,
+			DrawTextureParams {
+				dest_size: Some(rect.size()),
+				..Default::default()
+			},
+		);
+		// Outline of the part of the board which is on screen
+		let (board_width, board_height) = ui_state.board_pixel_size();
+		let camera = &ui_state.camera;
+		draw_rectangle_lines(
+			rect.x + camera.offset.0 / board_width * rect.w,
+			rect.y + camera.offset.1 / board_height * rect.h,
+			rect.w / camera.zoom,
+			rect.h / camera.zoom,
+			2.0,
+			YELLOW,
+		);
+	}
+}
+
+// One pixel per tile
+fn draw_tiles(image: &mut Image, board: &GameBoard, reveal_all: bool) {
+	for x in 0..board.width() {
+		for y in 0..board.height() {
+			let Some(tile) = board.get_tile(x, y) else {
+				continue;
+			};
+			let mine = if reveal_all { board.shown_mine(x, y) } else { None };
+			let color = match (tile.visible(), mine) {
+				(VisibleTile::Exploded, _) | (_, Some(true)) => Color::from_rgba(0, 0, 0, 255),
+				(VisibleTile::Revealed(adjacent), _) if adjacent > 0 => Color::from_rgba(150, 150, 200, 255),
+				(VisibleTile::Revealed(_), _) => Color::from_rgba(220, 220, 220, 255),
+				(VisibleTile::Flagged, _) => Color::from_rgba(220, 40, 40, 255),
+				_ => Color::from_rgba(110, 110, 110, 255),
+			};
+			image.set_pixel(x as u32, y as u32, color);
+		}
+	}
+}
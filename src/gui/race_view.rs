@@ -0,0 +1,61 @@
+use macroquad::prelude::*;
+
+use crate::{
+	logic::{game_board::GameBoard, GameState, Minesweeper},
+	net::race::{Outcome, Race},
+};
+
+use super::UIState;
+
+const GHOST_COLOR: Color = Color::new(0.9, 0.1, 0.1, 0.25);
+const GHOST_FLAG_COLOR: Color = Color::new(0.9, 0.1, 0.1, 0.6);
+const PLAYER_BAR_COLOR: Color = Color::new(0.1, 0.6, 0.1, 1.0);
+const OPPONENT_BAR_COLOR: Color = Color::new(0.85, 0.1, 0.1, 1.0);
+const LOST_BAR_COLOR: Color = Color::new(0.35, 0.35, 0.35, 1.0);
+const BAR_BACKGROUND: Color = Color::new(0.5, 0.5, 0.5, 1.0);
+// Height of each progress bar and the space around them, in unscaled pixels
+const BAR_HEIGHT: usize = 4;
+const BAR_GAP: usize = 2;
+const BAR_MARGIN: usize = 6;
+
+// Tints the tiles the opponent has revealed. Until this player's game is over, only tiles they have revealed
+// themselves are tinted, so the ghost never gives away which hidden tiles are safe.
+pub fn render_ghost(race: &Race, game: &Minesweeper, ui_state: &UIState) {
+	let finished = matches!(game.state, GameState::GameOver | GameState::Victory);
+	let tile_size = ui_state.scaled_tile_size();
+	let (xs, ys) = ui_state.visible_tiles();
+	for y in ys {
+		for x in xs.clone() {
+			let swept = game.board.get_tile(x, y).is_some_and(|tile| tile.swept);
+			if !finished && !swept {
+				continue;
+			}
+			let (screen_x, screen_y) = ui_state.tile_screen_position(x, y);
+			if race.opponent.revealed.contains(&(x, y)) {
+				draw_rectangle(screen_x, screen_y, tile_size, tile_size, GHOST_COLOR);
+			} else if finished && race.opponent.flags.contains(&(x, y)) {
+				draw_rectangle_lines(screen_x, screen_y, tile_size, tile_size, tile_size * 0.1, GHOST_FLAG_COLOR);
+			}
+		}
+	}
+}
+
+// This player's and the opponent's progress, as two bars along the bottom of the top bar.
+pub fn render_progress(race: &Race, board: &GameBoard, ui_state: &UIState) {
+	let board_width = ui_state.width * ui_state.tile_size;
+	let opponent_color = match race.opponent.outcome {
+		Some(Outcome::Lost) | Some(Outcome::Left) => LOST_BAR_COLOR,
+		_ => OPPONENT_BAR_COLOR,
+	};
+	let bars = [
+		(race.progress(board.revealed_tiles), PLAYER_BAR_COLOR),
+		(race.progress(race.opponent.revealed.len()), opponent_color),
+	];
+	for (n, (progress, color)) in bars.into_iter().enumerate() {
+		let y = ui_state.top_offset - BAR_MARGIN - (BAR_HEIGHT + BAR_GAP) * (bars.len() - n) + BAR_GAP;
+		let (x, y) = ui_state.pixel_screen_offset(BAR_MARGIN, y);
+		let (width, height) = ui_state.pixel_screen_scale(board_width - BAR_MARGIN * 2, BAR_HEIGHT);
+		draw_rectangle(x, y, width, height, BAR_BACKGROUND);
+		draw_rectangle(x, y, width * progress.min(1.0), height, color);
+	}
+}
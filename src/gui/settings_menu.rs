@@ -1,6 +1,15 @@
-use crate::{logic::game_board::ModifyMode, util::Events};
+use std::path::PathBuf;
 
-use super::{seven_segment::draw_seven_segment_unscaled, texture_store::TextureStore, GUIEvent, Language, UIState};
+use crate::{
+	logic::{
+		first_click::FirstClick,
+		game_board::{FlagRules, ModifyMode},
+		hot_seat::{MineRule, MAX_PLAYERS},
+	},
+	util::Events,
+};
+
+use super::{localization::Localization, seven_segment::draw_seven_segment_unscaled, texture_store::TextureStore, GUIEvent};
 use macroquad::{
 	hash,
 	prelude::*,
@@ -9,21 +18,49 @@ use macroquad::{
 
 const NEW_GAME_HEIGHT: f32 = 40f32;
 const NEW_GAME_WIDTH: f32 = 250f32;
-const BUTTON_MENU_WIDTH: f32 = 250f32;
+const ENDLESS_WIDTH: f32 = 150f32;
 const BUTTON_SIZE: f32 = 100f32;
+const BUTTON_MENU_GAP: f32 = 20f32;
+const BUTTON_MENU_COUNT: usize = 8;
+const BUTTON_MENU_WIDTH: f32 = BUTTON_SIZE * BUTTON_MENU_COUNT as f32 + BUTTON_MENU_GAP * (BUTTON_MENU_COUNT - 1) as f32;
+const VOLUME_LEVELS: [usize; 5] = [0, 25, 50, 75, 100];
+// Moves per second
+const AUTOPLAY_SPEEDS: [usize; 5] = [0, 2, 5, 15, 60];
 const BUTTON_MENU_Y: f32 = 400f32;
 const BUTTON_MENU_LABEL_HEIGHT: f32 = 20f32;
+const LANGUAGE_LIST_X: f32 = 20f32;
+const LANGUAGE_LIST_Y: f32 = 100f32;
+const LANGUAGE_BUTTON_WIDTH: f32 = 150f32;
+const LANGUAGE_BUTTON_HEIGHT: f32 = 30f32;
+const LANGUAGE_BUTTON_MARGIN: f32 = 5f32;
+const FLAG_RULE_Y: f32 = 100f32;
+const FLAG_RULE_WIDTH: f32 = 200f32;
+const FILE_Y: f32 = 540f32;
+const FILE_HEIGHT: f32 = 30f32;
+const FILE_PATH_WIDTH: f32 = 250f32;
+const FILE_BUTTON_WIDTH: f32 = 120f32;
+const FILE_MARGIN: f32 = 10f32;
 
 const MIN_MINEFIELD_WIDTH: usize = 5;
-const MAX_MINEFIELD_WIDTH: usize = 100;
+const MAX_MINEFIELD_WIDTH: usize = 1000;
 const MIN_MINEFIELD_HEIGHT: usize = 5;
-const MAX_MINEFIELD_HEIGHT: usize = 100;
+const MAX_MINEFIELD_HEIGHT: usize = 1000;
 
 pub struct SettingsMenu {
 	mines: usize,
 	width: usize,
 	height: usize,
 	board_modify_mode: ModifyMode,
+	animations: bool,
+	volume_level: usize,
+	file_path: String,
+	themes: Vec<(String, PathBuf)>,
+	theme: Option<usize>,
+	players: usize,
+	mine_rule: MineRule,
+	autoplay_speed: usize,
+	first_click: FirstClick,
+	flag_rules: FlagRules,
 }
 impl Default for SettingsMenu {
 	fn default() -> Self {
@@ -32,14 +69,40 @@ impl Default for SettingsMenu {
 			width: 30,
 			height: 16,
 			board_modify_mode: ModifyMode::Flag,
+			animations: true,
+			volume_level: VOLUME_LEVELS.len() - 1,
+			file_path: String::from("board.txt"),
+			themes: vec![],
+			theme: None,
+			players: 1,
+			mine_rule: MineRule::default(),
+			autoplay_speed: 0,
+			first_click: FirstClick::default(),
+			flag_rules: FlagRules::default(),
 		}
 	}
 }
 
 impl SettingsMenu {
+	// The board new games are started with
+	pub fn set_board(&mut self, width: usize, height: usize, mines: usize) {
+		self.width = width;
+		self.height = height;
+		self.mines = mines;
+	}
+
+	pub fn set_first_click(&mut self, first_click: FirstClick) {
+		self.first_click = first_click;
+	}
+
+	pub fn set_themes(&mut self, themes: Vec<(String, PathBuf)>) {
+		self.themes = themes;
+		self.theme = None;
+	}
+
 	pub fn render(
 		&mut self,
-		ui_state: &UIState,
+		localization: &Localization,
 		event_handler: &mut Events<GUIEvent>,
 		textures: &TextureStore,
 		skin: &Skin,
@@ -64,7 +127,7 @@ impl SettingsMenu {
 				ui,
 				textures,
 				vec2(half_screen_width, 100f32),
-				"Minefield Width",
+				localization.get("settings.width"),
 				MIN_MINEFIELD_WIDTH,
 				MAX_MINEFIELD_WIDTH,
 			);
@@ -73,7 +136,7 @@ impl SettingsMenu {
 				ui,
 				textures,
 				vec2(half_screen_width, 200f32),
-				"Minefield Height",
+				localization.get("settings.height"),
 				MIN_MINEFIELD_HEIGHT,
 				MAX_MINEFIELD_HEIGHT,
 			);
@@ -82,60 +145,248 @@ impl SettingsMenu {
 				ui,
 				textures,
 				vec2(half_screen_width, 300f32),
-				"Mines",
+				localization.get("settings.mines"),
 				1,
-				self.width * self.height - 10,
+				(self.width * self.height).saturating_sub(self.first_click.reserved_tiles()).max(1),
 			);
-			if widgets::Button::new("New Game")
+			// Small boards have no room for even one mine under the rules which keep the most tiles clear
+			let has_room = self.width * self.height > self.first_click.reserved_tiles();
+			if widgets::Button::new(localization.get("settings.new_game"))
 				.size(vec2(NEW_GAME_WIDTH, NEW_GAME_HEIGHT))
 				.position(vec2((screen_width - NEW_GAME_WIDTH) * 0.5, 0.0))
-				.ui(ui)
+				.ui(ui) && has_room
 			{
 				event_handler.add(GUIEvent::CreateNewGame(self.width, self.height, self.mines));
 				event_handler.add(GUIEvent::CloseSettings);
 			}
-			let language_button_x = (screen_width - BUTTON_MENU_WIDTH) * 0.5;
-			let question_button_x = (screen_width - BUTTON_MENU_WIDTH) * 0.5 + (BUTTON_MENU_WIDTH - BUTTON_SIZE);
-
-			widgets::Label::new("Language")
-				.position(vec2(language_button_x, BUTTON_MENU_Y - BUTTON_MENU_LABEL_HEIGHT))
-				.size(vec2(BUTTON_SIZE, BUTTON_MENU_LABEL_HEIGHT))
+			if widgets::Button::new(localization.get("settings.endless"))
+				.size(vec2(ENDLESS_WIDTH, NEW_GAME_HEIGHT))
+				.position(vec2((screen_width + NEW_GAME_WIDTH) * 0.5 + 10.0, 0.0))
+				.ui(ui)
+			{
+				event_handler.add(GUIEvent::StartEndless);
+				event_handler.add(GUIEvent::CloseSettings);
+			}
+			// The flag rules are listed down the right, across from the languages
+			let flag_rule_x = screen_width - LANGUAGE_LIST_X - FLAG_RULE_WIDTH;
+			widgets::Label::new(localization.get("settings.flags"))
+				.position(vec2(flag_rule_x, FLAG_RULE_Y - BUTTON_MENU_LABEL_HEIGHT))
+				.size(vec2(FLAG_RULE_WIDTH, BUTTON_MENU_LABEL_HEIGHT))
 				.ui(ui);
-			widgets::Label::new("Question Marking")
-				.position(vec2(question_button_x, BUTTON_MENU_Y - BUTTON_MENU_LABEL_HEIGHT))
-				.size(vec2(BUTTON_SIZE, BUTTON_MENU_LABEL_HEIGHT))
+			let flag_rules = [
+				("settings.flag_limit", &mut self.flag_rules.limit),
+				("settings.flag_on_win", &mut self.flag_rules.flag_on_win),
+				("settings.flag_obvious", &mut self.flag_rules.flag_obvious),
+			];
+			let mut flag_rules_changed = false;
+			for (n, (key, enabled)) in flag_rules.into_iter().enumerate() {
+				let state = if *enabled { "settings.on" } else { "settings.off" };
+				let label = format!("{}: {}", localization.get(key), localization.get(state));
+				let y = FLAG_RULE_Y + (LANGUAGE_BUTTON_HEIGHT + LANGUAGE_BUTTON_MARGIN) * n as f32;
+				if widgets::Button::new(label.as_str())
+					.size(vec2(FLAG_RULE_WIDTH, LANGUAGE_BUTTON_HEIGHT))
+					.position(vec2(flag_rule_x, y))
+					.ui(ui)
+				{
+					*enabled = !*enabled;
+					flag_rules_changed = true;
+				}
+			}
+			if flag_rules_changed {
+				event_handler.add(GUIEvent::SetFlagRules(self.flag_rules));
+			}
+
+			let button_menu_x = |n: usize| (screen_width - BUTTON_MENU_WIDTH) * 0.5 + (BUTTON_SIZE + BUTTON_MENU_GAP) * n as f32;
+			let animation_button_x = button_menu_x(0);
+			let sound_button_x = button_menu_x(1);
+			let question_button_x = button_menu_x(2);
+			let theme_button_x = button_menu_x(3);
+			let players_button_x = button_menu_x(4);
+			let mine_rule_button_x = button_menu_x(5);
+			let autoplay_button_x = button_menu_x(6);
+			let first_click_button_x = button_menu_x(7);
+
+			for (key, x) in [
+				("settings.animations", animation_button_x),
+				("settings.sound", sound_button_x),
+				("settings.question_marking", question_button_x),
+				("settings.theme", theme_button_x),
+				("settings.players", players_button_x),
+				("settings.mine_hits", mine_rule_button_x),
+				("settings.autoplay", autoplay_button_x),
+				("settings.first_click", first_click_button_x),
+			] {
+				widgets::Label::new(localization.get(key))
+					.position(vec2(x, BUTTON_MENU_Y - BUTTON_MENU_LABEL_HEIGHT))
+					.size(vec2(BUTTON_SIZE, BUTTON_MENU_LABEL_HEIGHT))
+					.ui(ui);
+			}
+
+			widgets::Label::new(localization.get("settings.language"))
+				.position(vec2(LANGUAGE_LIST_X, LANGUAGE_LIST_Y - BUTTON_MENU_LABEL_HEIGHT))
+				.size(vec2(LANGUAGE_BUTTON_WIDTH, BUTTON_MENU_LABEL_HEIGHT))
 				.ui(ui);
-			if let Language::English = ui_state.language {
-				if widgets::Button::new("English")
-					.size(vec2(BUTTON_SIZE, BUTTON_SIZE))
-					.position(vec2(language_button_x, BUTTON_MENU_Y))
+			for (n, name) in localization.names().enumerate() {
+				let label = if n == localization.current_index() {
+					format!("> {}", name)
+				} else {
+					name.to_string()
+				};
+				let y = LANGUAGE_LIST_Y + (LANGUAGE_BUTTON_HEIGHT + LANGUAGE_BUTTON_MARGIN) * n as f32;
+				if widgets::Button::new(label.as_str())
+					.size(vec2(LANGUAGE_BUTTON_WIDTH, LANGUAGE_BUTTON_HEIGHT))
+					.position(vec2(LANGUAGE_LIST_X, y))
 					.ui(ui)
 				{
-					event_handler.add(GUIEvent::SwitchLanguage(Language::Japanese));
+					event_handler.add(GUIEvent::SwitchLanguage(n));
 				}
-			} else if widgets::Button::new("Japanese")
+			}
+
+			let question_label = match self.board_modify_mode {
+				ModifyMode::Flag => "settings.off",
+				ModifyMode::Question => "settings.marks_cycle",
+				ModifyMode::Separate => "settings.marks_key",
+			};
+			if widgets::Button::new(localization.get(question_label))
 				.size(vec2(BUTTON_SIZE, BUTTON_SIZE))
-				.position(vec2(language_button_x, BUTTON_MENU_Y))
+				.position(vec2(question_button_x, BUTTON_MENU_Y))
 				.ui(ui)
 			{
-				event_handler.add(GUIEvent::SwitchLanguage(Language::English));
+				self.board_modify_mode = match self.board_modify_mode {
+					ModifyMode::Flag => ModifyMode::Question,
+					ModifyMode::Question => ModifyMode::Separate,
+					ModifyMode::Separate => ModifyMode::Flag,
+				};
+				event_handler.add(GUIEvent::SetModifyMode(self.board_modify_mode.clone()));
 			}
-			if let ModifyMode::Question = self.board_modify_mode {
-				if widgets::Button::new("ON")
-					.size(vec2(BUTTON_SIZE, BUTTON_SIZE))
-					.position(vec2(question_button_x, BUTTON_MENU_Y))
-					.ui(ui)
-				{
-					self.board_modify_mode = ModifyMode::Flag;
-					event_handler.add(GUIEvent::SetQuestionMode(ModifyMode::Flag));
+			let animation_label = if self.animations { "settings.on" } else { "settings.off" };
+			if widgets::Button::new(localization.get(animation_label))
+				.size(vec2(BUTTON_SIZE, BUTTON_SIZE))
+				.position(vec2(animation_button_x, BUTTON_MENU_Y))
+				.ui(ui)
+			{
+				self.animations = !self.animations;
+				event_handler.add(GUIEvent::SetAnimations(self.animations));
+			}
+			let volume = VOLUME_LEVELS[self.volume_level];
+			let volume_label = if volume == 0 {
+				localization.get("settings.off").to_string()
+			} else {
+				format!("{}%", volume)
+			};
+			if widgets::Button::new(volume_label.as_str())
+				.size(vec2(BUTTON_SIZE, BUTTON_SIZE))
+				.position(vec2(sound_button_x, BUTTON_MENU_Y))
+				.ui(ui)
+			{
+				self.volume_level = (self.volume_level + 1) % VOLUME_LEVELS.len();
+				event_handler.add(GUIEvent::SetVolume(VOLUME_LEVELS[self.volume_level] as f32 / 100.0));
+			}
+			let theme_label = match self.theme {
+				Some(theme) => self.themes[theme].0.as_str(),
+				None => localization.get("settings.default_theme"),
+			};
+			if widgets::Button::new(theme_label)
+				.size(vec2(BUTTON_SIZE, BUTTON_SIZE))
+				.position(vec2(theme_button_x, BUTTON_MENU_Y))
+				.ui(ui)
+			{
+				// Cycles through each theme, then back to the built in sprites
+				self.theme = match self.theme {
+					None if !self.themes.is_empty() => Some(0),
+					Some(theme) if theme + 1 < self.themes.len() => Some(theme + 1),
+					_ => None,
+				};
+				event_handler.add(GUIEvent::SwitchTheme(self.theme.map(|theme| self.themes[theme].1.clone())));
+			}
+			if widgets::Button::new(self.players.to_string().as_str())
+				.size(vec2(BUTTON_SIZE, BUTTON_SIZE))
+				.position(vec2(players_button_x, BUTTON_MENU_Y))
+				.ui(ui)
+			{
+				self.players = self.players % MAX_PLAYERS + 1;
+				event_handler.add(GUIEvent::SetHotSeat(self.players, self.mine_rule));
+			}
+			let mine_rule_label = match self.mine_rule {
+				MineRule::Penalty => "settings.penalty",
+				MineRule::Eliminate => "settings.eliminate",
+			};
+			if widgets::Button::new(localization.get(mine_rule_label))
+				.size(vec2(BUTTON_SIZE, BUTTON_SIZE))
+				.position(vec2(mine_rule_button_x, BUTTON_MENU_Y))
+				.ui(ui)
+			{
+				self.mine_rule = match self.mine_rule {
+					MineRule::Penalty => MineRule::Eliminate,
+					MineRule::Eliminate => MineRule::Penalty,
+				};
+				// A single player's game has no use for the rule, so it isn't restarted for it. The rule is sent once
+				// there are more players.
+				if self.players > 1 {
+					event_handler.add(GUIEvent::SetHotSeat(self.players, self.mine_rule));
 				}
-			} else if widgets::Button::new("OFF")
+			}
+			let autoplay_speed = AUTOPLAY_SPEEDS[self.autoplay_speed];
+			let autoplay_label = if autoplay_speed == 0 {
+				localization.get("settings.off").to_string()
+			} else {
+				format!("{}/s", autoplay_speed)
+			};
+			if widgets::Button::new(autoplay_label.as_str())
 				.size(vec2(BUTTON_SIZE, BUTTON_SIZE))
-				.position(vec2(question_button_x, BUTTON_MENU_Y))
+				.position(vec2(autoplay_button_x, BUTTON_MENU_Y))
+				.ui(ui)
+			{
+				self.autoplay_speed = (self.autoplay_speed + 1) % AUTOPLAY_SPEEDS.len();
+				event_handler.add(GUIEvent::SetAutoplay(AUTOPLAY_SPEEDS[self.autoplay_speed]));
+			}
+			let first_click_label = match self.first_click {
+				FirstClick::Unprotected => "settings.first_click_none",
+				FirstClick::Safe => "settings.first_click_safe",
+				FirstClick::Opening => "settings.first_click_opening",
+				FirstClick::Area => "settings.first_click_area",
+			};
+			if widgets::Button::new(localization.get(first_click_label))
+				.size(vec2(BUTTON_SIZE, BUTTON_SIZE))
+				.position(vec2(first_click_button_x, BUTTON_MENU_Y))
 				.ui(ui)
 			{
-				self.board_modify_mode = ModifyMode::Question;
-				event_handler.add(GUIEvent::SetQuestionMode(ModifyMode::Question));
+				// Rules which leave no room for the mines set above are skipped
+				let current = FirstClick::ALL.iter().position(|&first_click| first_click == self.first_click).unwrap_or(0);
+				let next = (1..FirstClick::ALL.len())
+					.map(|n| FirstClick::ALL[(current + n) % FirstClick::ALL.len()])
+					.find(|first_click| self.mines + first_click.reserved_tiles() <= self.width * self.height);
+				if let Some(next) = next {
+					self.first_click = next;
+					event_handler.add(GUIEvent::SetFirstClick(next));
+				}
+			}
+
+			let file_buttons = [
+				("settings.load_board", GUIEvent::LoadBoard as fn(String) -> GUIEvent),
+				("settings.save_board", GUIEvent::SaveBoard),
+				("settings.load_replay", GUIEvent::LoadReplay),
+				("settings.save_replay", GUIEvent::SaveReplay),
+				("settings.save_image", GUIEvent::SaveImage),
+			];
+			let file_row_width = FILE_PATH_WIDTH + (FILE_BUTTON_WIDTH + FILE_MARGIN) * file_buttons.len() as f32;
+			let file_x = (screen_width - file_row_width) * 0.5;
+			widgets::Label::new(localization.get("settings.file"))
+				.position(vec2(file_x, FILE_Y - BUTTON_MENU_LABEL_HEIGHT))
+				.size(vec2(FILE_PATH_WIDTH, BUTTON_MENU_LABEL_HEIGHT))
+				.ui(ui);
+			widgets::Editbox::new(hash!(), vec2(FILE_PATH_WIDTH, FILE_HEIGHT))
+				.position(vec2(file_x, FILE_Y))
+				.ui(ui, &mut self.file_path);
+			for (n, (key, event)) in file_buttons.into_iter().enumerate() {
+				let button_x = file_x + FILE_PATH_WIDTH + FILE_MARGIN + (FILE_BUTTON_WIDTH + FILE_MARGIN) * n as f32;
+				if widgets::Button::new(localization.get(key))
+					.size(vec2(FILE_BUTTON_WIDTH, FILE_HEIGHT))
+					.position(vec2(button_x, FILE_Y))
+					.ui(ui)
+				{
+					event_handler.add(event(self.file_path.clone()));
+				}
 			}
 		});
 	}
@@ -147,6 +398,7 @@ const COUNTER_BUTTON_HEIGHT: f32 = 30f32;
 const COUNTER_BUTTON_MARGIN: f32 = 10f32;
 const BUTTON_OFFSET_HEIGHT: f32 = (COUNTER_DIGIT_HEIGHT - COUNTER_BUTTON_HEIGHT) * 0.5;
 
+#[rustfmt::skip]
 fn render_counter(count: &mut usize, ui: &mut Ui, textures: &TextureStore, position: Vec2, title: &str, min: usize, max: usize) {
 	let digits: Vec<usize> = {
 		let digits = count.to_string();
@@ -155,6 +407,8 @@ fn render_counter(count: &mut usize, ui: &mut Ui, textures: &TextureStore, posit
 	};
 
 	let counter_width = digits.len() as f32 * COUNTER_DIGIT_WIDTH;
+	// The big buttons change the second digit from the left, so large counts don't take hundreds of clicks
+	let big_step = 10usize.pow(digits.len().saturating_sub(2) as u32).max(10);
 	let position = position - vec2(counter_width * 0.5, 0.0);
 
 	draw_seven_segment_unscaled(ui, textures, &digits, position.x as usize, position.y as usize);
@@ -183,15 +437,15 @@ fn render_counter(count: &mut usize, ui: &mut Ui, textures: &TextureStore, posit
 		)
 		.ui(ui)
 	{
-		*count += 10;
+		*count += big_step;
 	}
 	if widgets::Button::new("--")
 		.size(vec2(COUNTER_BUTTON_HEIGHT, COUNTER_BUTTON_HEIGHT))
 		.position(position - vec2((COUNTER_BUTTON_HEIGHT + COUNTER_BUTTON_MARGIN) * 2.0, -BUTTON_OFFSET_HEIGHT))
 		.ui(ui)
 	{
-		if *count as isize - 10 > min as isize {
-			*count -= 10;
+		if *count as isize - big_step as isize > min as isize {
+			*count -= big_step;
 		} else {
 			*count = min;
 		}
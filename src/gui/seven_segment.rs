@@ -19,6 +19,19 @@ pub fn draw_seven_segment(ui_state: &UIState, ui: &mut Ui, textures: &TextureSto
 	}
 }
 
+// Half the size of `draw_seven_segment`, for when several numbers share the space of one
+pub fn draw_small_seven_segment(ui_state: &UIState, ui: &mut Ui, textures: &TextureStore, val: &[usize], x: usize, y: usize) {
+	for (n, digit) in val.iter().enumerate() {
+		let (scaled_width, scaled_height) = ui_state.pixel_screen_scale(WIDTH / 2, HEIGHT / 2);
+		let (pos_x, pos_y) = ui_state.pixel_screen_offset(n * WIDTH / 2 + x, y);
+
+		widgets::Texture::new(textures.numbers[*digit])
+			.size(scaled_width, scaled_height)
+			.position(vec2(pos_x, pos_y))
+			.ui(ui);
+	}
+}
+
 pub fn draw_seven_segment_unscaled(ui: &mut Ui, textures: &TextureStore, val: &[usize], x: usize, y: usize) {
 	for (n, digit) in val.iter().enumerate() {
 		let (pos_x, pos_y) = ((n * WIDTH + x) as f32, y as f32);
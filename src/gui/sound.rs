@@ -0,0 +1,149 @@
+use macroquad::audio::{load_sound_from_bytes, play_sound, PlaySoundParams, Sound};
+
+use crate::{
+	logic::{endless::EndlessEvent, GameEvent},
+	util::Subscriber,
+};
+
+#[derive(Copy, Clone, PartialEq)]
+enum Effect {
+	Reveal,
+	Cascade,
+	Flag,
+	Chord,
+	Explosion,
+	Victory,
+	Smiley,
+}
+
+const EFFECT_FILES: [(Effect, &[u8]); 7] = [
+	(Effect::Reveal, include_bytes!("../../assets/sounds/reveal.wav")),
+	(Effect::Cascade, include_bytes!("../../assets/sounds/cascade.wav")),
+	(Effect::Flag, include_bytes!("../../assets/sounds/flag.wav")),
+	(Effect::Chord, include_bytes!("../../assets/sounds/chord.wav")),
+	(Effect::Explosion, include_bytes!("../../assets/sounds/explosion.wav")),
+	(Effect::Victory, include_bytes!("../../assets/sounds/victory.wav")),
+	(Effect::Smiley, include_bytes!("../../assets/sounds/smiley.wav")),
+];
+
+// Plays sound effects for game events. Effects requested by one frame's events are played together
+// once per frame, so a cascade of reveals makes one sound instead of hundreds.
+#[derive(Default)]
+pub struct SoundPlayer {
+	sounds: Vec<(Effect, Sound)>,
+	volume: f32,
+	queued: Vec<Effect>,
+	sweep_reveals: usize,
+}
+
+impl SoundPlayer {
+	pub async fn load(volume: f32) -> Self {
+		let mut sounds = vec![];
+		// Without an output device macroquad's audio thread has already died, and every sound sent
+		// to it would just print an error. Skip loading so the game stays silent instead.
+		if audio_device_available() {
+			for (effect, bytes) in EFFECT_FILES {
+				match load_sound_from_bytes(bytes).await {
+					Ok(sound) => sounds.push((effect, sound)),
+					Err(error) => eprintln!("Could not load sound: {}", error),
+				}
+			}
+		}
+		Self {
+			sounds,
+			volume,
+			..Default::default()
+		}
+	}
+
+	// Zero mutes all sounds
+	pub fn set_volume(&mut self, volume: f32) {
+		self.volume = volume.clamp(0.0, 1.0);
+	}
+
+	pub fn play_queued(&mut self) {
+		if self.volume <= 0.0 {
+			self.queued.clear();
+			return;
+		}
+		if self.queued.contains(&Effect::Explosion) {
+			self.queued.retain(|&effect| effect != Effect::Reveal && effect != Effect::Cascade);
+		}
+		if self.queued.contains(&Effect::Cascade) {
+			self.queued.retain(|&effect| effect != Effect::Reveal);
+		}
+		for effect in self.queued.drain(..) {
+			if let Some((_, sound)) = self.sounds.iter().find(|(loaded, _)| *loaded == effect) {
+				play_sound(
+					*sound,
+					PlaySoundParams {
+						looped: false,
+						volume: self.volume,
+					},
+				);
+			}
+		}
+	}
+
+	fn queue(&mut self, effect: Effect) {
+		if !self.queued.contains(&effect) {
+			self.queued.push(effect);
+		}
+	}
+}
+
+impl Subscriber<GameEvent> for SoundPlayer {
+	fn notify(&mut self, event: &GameEvent) {
+		match event {
+			GameEvent::RevealTile(_, _, _) => self.sweep_reveals += 1,
+			GameEvent::SweepDone => {
+				match self.sweep_reveals {
+					0 => (),
+					1 => self.queue(Effect::Reveal),
+					_ => self.queue(Effect::Cascade),
+				}
+				self.sweep_reveals = 0;
+			}
+			GameEvent::FlagTile(_, _, _) | GameEvent::QuestionTile(_, _, _) => self.queue(Effect::Flag),
+			GameEvent::Chord(_, _) => self.queue(Effect::Chord),
+			GameEvent::Lose(_, _, _) | GameEvent::Detonate(_, _, _) => self.queue(Effect::Explosion),
+			GameEvent::Win => self.queue(Effect::Victory),
+			GameEvent::Reset => {
+				self.sweep_reveals = 0;
+				self.queue(Effect::Smiley);
+			}
+			_ => (),
+		}
+	}
+}
+
+impl Subscriber<EndlessEvent> for SoundPlayer {
+	fn notify(&mut self, event: &EndlessEvent) {
+		match event {
+			EndlessEvent::Reveal(_, _, 1) => self.queue(Effect::Reveal),
+			EndlessEvent::Reveal(_, _, _) => self.queue(Effect::Cascade),
+			EndlessEvent::Flag(_, _) => self.queue(Effect::Flag),
+			EndlessEvent::Chord(_, _) => self.queue(Effect::Chord),
+			EndlessEvent::Lose(_, _) => self.queue(Effect::Explosion),
+			EndlessEvent::Reset => self.queue(Effect::Smiley),
+		}
+	}
+}
+
+#[cfg(target_os = "linux")]
+fn audio_device_available() -> bool {
+	// ALSA playback devices show up as /dev/snd/pcmC<card>D<device>p
+	match std::fs::read_dir("/dev/snd") {
+		Ok(entries) => entries.flatten().any(|entry| {
+			let name = entry.file_name();
+			let name = name.to_string_lossy();
+			name.starts_with("pcm") && name.ends_with('p')
+		}),
+		Err(_) => false,
+	}
+}
+
+#[cfg(not(target_os = "linux"))]
+fn audio_device_available() -> bool {
+	true
+}
@@ -1,17 +1,90 @@
 use image::ImageFormat;
 use macroquad::texture::Texture2D;
 
-use crate::sprite_loader::load_sprites;
+use std::error::Error;
 
-use super::Language;
+use crate::sprite_loader::{load_sprites, sheet_layout, SheetLayout, SpriteAtlas};
+
+use super::theme::Theme;
+
+pub const DEFAULT_TILES: &[u8] = include_bytes!("../../assets/english_32x.png");
+pub const DEFAULT_TILE_SHEETS: [&[u8]; 3] = [
+	include_bytes!("../../assets/english_16x.png"),
+	DEFAULT_TILES,
+	include_bytes!("../../assets/english_64x.png"),
+];
+pub const JAPANESE_TILE_SHEETS: [&[u8]; 3] = [
+	include_bytes!("../../assets/japanese_16x.png"),
+	include_bytes!("../../assets/japanese_32x.png"),
+	include_bytes!("../../assets/japanese_64x.png"),
+];
+const NUMBERS: &[u8] = include_bytes!("../../assets/numbers.png");
+const FACES: &[u8] = include_bytes!("../../assets/faces.png");
+
+// One sprite per `TileIndex`. Tile sheets may come in any square tile size, see `TileSet`.
+pub const TILE_LAYOUT: SheetLayout = SheetLayout {
+	tile_size: (32, 32),
+	rows: 2,
+	columns: 8,
+};
+// Digits 0 through 9, followed by the minus sign
+pub const NUMBER_LAYOUT: SheetLayout = SheetLayout {
+	tile_size: (26, 46),
+	rows: 1,
+	columns: 11,
+};
+// One sprite per `SmileyState`
+pub const FACE_LAYOUT: SheetLayout = SheetLayout {
+	tile_size: (48, 48),
+	rows: 1,
+	columns: 5,
+};
+
+// The same tiles drawn at several resolutions, so the board can be drawn from whichever is closest to its size on screen.
+#[derive(Clone)]
+pub struct TileSet {
+	// Sorted by tile size, smallest first
+	sheets: Vec<(u32, SpriteAtlas)>,
+}
+
+impl TileSet {
+	// Each sheet holds the tiles in a grid of `rows` by `columns`, with the tile size worked out from the sheet's dimensions.
+	pub fn load<'a>(sheets: impl IntoIterator<Item = &'a [u8]>, rows: usize, columns: usize) -> Result<Self, Box<dyn Error>> {
+		let mut loaded = vec![];
+		for sheet in sheets {
+			let layout = sheet_layout(sheet, rows, columns)?;
+			let (tile_width, tile_height) = layout.tile_size;
+			if tile_width != tile_height {
+				return Err(format!("Tiles must be square, but are {}x{}px", tile_width, tile_height).into());
+			}
+			loaded.push((tile_width, SpriteAtlas::load(sheet, layout)?));
+		}
+		if loaded.is_empty() {
+			return Err("No tile sheets given".into());
+		}
+		loaded.sort_by_key(|(size, _)| *size);
+		Ok(Self { sheets: loaded })
+	}
+
+	// The smallest tiles which are at least `pixel_size` physical pixels wide, so they are only ever scaled down.
+	// Falls back to the largest tiles when drawing bigger than any sheet.
+	pub fn get(&self, pixel_size: f32) -> &SpriteAtlas {
+		let (_, tiles) = self
+			.sheets
+			.iter()
+			.find(|(size, _)| *size as f32 >= pixel_size)
+			.unwrap_or_else(|| self.sheets.last().expect("TileSet is never empty"));
+		tiles
+	}
+}
 
 pub struct TextureStore {
-	english_tiles: Vec<Texture2D>,
-	japanese_tiles: Vec<Texture2D>,
+	default_tiles: TileSet,
+	language_tiles: Option<TileSet>,
+	theme_tiles: Option<TileSet>,
 	pub numbers: Vec<Texture2D>,
 	pub smilies: Vec<Texture2D>,
 	pub cog: Texture2D,
-	pub lang: Language,
 }
 impl Default for TextureStore {
 	fn default() -> Self {
@@ -22,20 +95,48 @@ impl Default for TextureStore {
 impl TextureStore {
 	pub fn new() -> Self {
 		Self {
-			numbers: load_sprites(include_bytes!("../../assets/numbers.png"), (26, 46), 1, 10).expect("Could not load sprites"),
-			english_tiles: load_sprites(include_bytes!("../../assets/english_32x.png"), (32, 32), 2, 8)
+			numbers: load_sprites(NUMBERS, NUMBER_LAYOUT).expect("Could not load sprites"),
+			default_tiles: TileSet::load(DEFAULT_TILE_SHEETS, TILE_LAYOUT.rows, TILE_LAYOUT.columns)
 				.expect("Could not load Tile Sprites"),
-			japanese_tiles: load_sprites(include_bytes!("../../assets/japanese_32x.png"), (32, 32), 2, 8)
-				.expect("Could not load Tile Sprites"),
-			smilies: load_sprites(include_bytes!("../../assets/faces.png"), (48, 48), 1, 5).expect("Could not load face sprites"),
+			language_tiles: None,
+			theme_tiles: None,
+			smilies: load_sprites(FACES, FACE_LAYOUT).expect("Could not load face sprites"),
 			cog: Texture2D::from_file_with_format(include_bytes!("../../assets/cog.png"), Some(ImageFormat::Png)),
-			lang: Language::English,
 		}
 	}
-	pub fn get_tiles(&self) -> &Vec<Texture2D> {
-		match self.lang {
-			Language::English => &self.english_tiles,
-			Language::Japanese => &self.japanese_tiles,
+	// Tiles come from the theme if one is set, then the language, then the built in English sheet.
+	// `pixel_size` is how many physical pixels wide the tiles are drawn.
+	pub fn get_tiles(&self, pixel_size: f32) -> &SpriteAtlas {
+		self.theme_tiles
+			.as_ref()
+			.or(self.language_tiles.as_ref())
+			.unwrap_or(&self.default_tiles)
+			.get(pixel_size)
+	}
+	pub fn set_language_tiles(&mut self, tile_sheets: &[Vec<u8>]) {
+		if tile_sheets.is_empty() {
+			self.language_tiles = None;
+			return;
 		}
+		self.language_tiles = match TileSet::load(tile_sheets.iter().map(Vec::as_slice), TILE_LAYOUT.rows, TILE_LAYOUT.columns) {
+			Ok(tiles) => Some(tiles),
+			Err(error) => {
+				eprintln!("Could not load language tile sheet: {}", error);
+				None
+			}
+		};
+	}
+	// Swaps in the sheets of a theme, or goes back to the built in sprites when given None.
+	// Sheets a theme leaves out keep using the built in sprites.
+	pub fn set_theme(&mut self, theme: Option<&Theme>) {
+		self.theme_tiles = theme.map(|theme| theme.tiles.clone());
+		self.numbers = match theme.and_then(|theme| theme.numbers.as_ref()) {
+			Some(numbers) => numbers.clone(),
+			None => load_sprites(NUMBERS, NUMBER_LAYOUT).expect("Could not load sprites"),
+		};
+		self.smilies = match theme.and_then(|theme| theme.faces.as_ref()) {
+			Some(faces) => faces.clone(),
+			None => load_sprites(FACES, FACE_LAYOUT).expect("Could not load face sprites"),
+		};
 	}
 }
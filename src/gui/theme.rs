@@ -0,0 +1,142 @@
+use std::{
+	collections::HashMap,
+	error::Error,
+	fs,
+	path::{Path, PathBuf},
+};
+
+use macroquad::texture::Texture2D;
+
+use crate::{
+	sprite_loader::{load_sprites, sheet_layout, SheetLayout},
+	util::parse_key_values,
+};
+
+use super::texture_store::{TileSet, FACE_LAYOUT, NUMBER_LAYOUT, TILE_LAYOUT};
+
+pub const THEME_DIRECTORY: &str = "themes";
+const MANIFEST_FILE: &str = "theme.txt";
+
+// A theme pack is a directory holding a `theme.txt` manifest and the sprite sheets it names:
+//
+//     name = Dark
+//     tiles = tiles_32x.png, tiles_64x.png
+//     tiles_grid = 8x2
+//     numbers = numbers.png
+//     numbers_size = 26x46
+//     numbers_grid = 11x1
+//     faces = faces.png
+//     faces_size = 48x48
+//     faces_grid = 5x1
+//
+// Grids are given as COLUMNSxROWS. Sizes are optional and worked out from the grid when left out.
+// `tiles` may list the same sheet at several resolutions, and its tiles must be square.
+// Only `tiles` is required; the other sheets fall back to the built in sprites.
+// Each sheet must hold exactly as many sprites as the built in sheet it replaces.
+pub struct Theme {
+	pub tiles: TileSet,
+	pub numbers: Option<Vec<Texture2D>>,
+	pub faces: Option<Vec<Texture2D>>,
+}
+
+impl Theme {
+	pub fn load(directory: &Path) -> Result<Self, Box<dyn Error>> {
+		let manifest_path = directory.join(MANIFEST_FILE);
+		let manifest = fs::read_to_string(&manifest_path).map_err(|error| format!("{}: {}", manifest_path.display(), error))?;
+		let manifest = parse_key_values(&manifest).map_err(|error| format!("{}: {}", manifest_path.display(), error))?;
+
+		Ok(Self {
+			tiles: load_tile_set(directory, &manifest)?,
+			numbers: load_sheet(directory, &manifest, "numbers", NUMBER_LAYOUT)?,
+			faces: load_sheet(directory, &manifest, "faces", FACE_LAYOUT)?,
+		})
+	}
+
+	// The name and path of every directory in `directory` which has a theme manifest, sorted by path.
+	// Themes without a `name` are named after their directory.
+	pub fn discover(directory: &Path) -> Vec<(String, PathBuf)> {
+		let Ok(entries) = fs::read_dir(directory) else {
+			return vec![];
+		};
+		let mut themes: Vec<(String, PathBuf)> = entries
+			.flatten()
+			.map(|entry| entry.path())
+			.filter_map(|path| {
+				let manifest = fs::read_to_string(path.join(MANIFEST_FILE)).ok()?;
+				let name = match parse_key_values(&manifest).ok().and_then(|mut manifest| manifest.remove("name")) {
+					Some(name) => name,
+					None => path.file_name()?.to_string_lossy().to_string(),
+				};
+				Some((name, path))
+			})
+			.collect();
+		themes.sort_by(|a, b| a.1.cmp(&b.1));
+		themes
+	}
+}
+
+fn load_tile_set(directory: &Path, manifest: &HashMap<String, String>) -> Result<TileSet, Box<dyn Error>> {
+	let files = manifest.get("tiles").ok_or("Theme manifest is missing `tiles`")?;
+	let (columns, rows) = sheet_grid(manifest, "tiles", TILE_LAYOUT)?;
+	let mut sheets = vec![];
+	for file in files.split(',').map(str::trim) {
+		let path = directory.join(file);
+		sheets.push(fs::read(&path).map_err(|error| format!("{}: {}", path.display(), error))?);
+	}
+	TileSet::load(sheets.iter().map(Vec::as_slice), rows, columns)
+}
+
+fn load_sheet(
+	directory: &Path,
+	manifest: &HashMap<String, String>,
+	sheet: &str,
+	default_layout: SheetLayout,
+) -> Result<Option<Vec<Texture2D>>, Box<dyn Error>> {
+	let Some(file) = manifest.get(sheet) else {
+		return Ok(None);
+	};
+	let (columns, rows) = sheet_grid(manifest, sheet, default_layout)?;
+	let path = directory.join(file);
+	let bytes = fs::read(&path).map_err(|error| format!("{}: {}", path.display(), error))?;
+	let layout = match manifest.get(&format!("{}_size", sheet)) {
+		Some(size) => SheetLayout {
+			tile_size: parse_pair(size).map_err(|error| format!("`{}_size`: {}", sheet, error))?,
+			rows,
+			columns,
+		},
+		None => sheet_layout(&bytes, rows, columns).map_err(|error| format!("{}: {}", path.display(), error))?,
+	};
+	let sprites = load_sprites(&bytes, layout).map_err(|error| format!("{}: {}", path.display(), error))?;
+	Ok(Some(sprites))
+}
+
+// The COLUMNSxROWS grid of a sheet, which must hold as many sprites as the built in sheet.
+fn sheet_grid(
+	manifest: &HashMap<String, String>,
+	sheet: &str,
+	default_layout: SheetLayout,
+) -> Result<(usize, usize), Box<dyn Error>> {
+	let (columns, rows) = match manifest.get(&format!("{}_grid", sheet)) {
+		Some(grid) => parse_pair(grid).map_err(|error| format!("`{}_grid`: {}", sheet, error))?,
+		None => (default_layout.columns as u32, default_layout.rows as u32),
+	};
+	let (columns, rows) = (columns as usize, rows as usize);
+	if columns * rows != default_layout.sprite_count() {
+		return Err(format!(
+			"`{}` has {} sprites ({}x{}), but needs {}",
+			sheet,
+			columns * rows,
+			columns,
+			rows,
+			default_layout.sprite_count()
+		)
+		.into());
+	}
+	Ok((columns, rows))
+}
+
+// Parses sizes such as `32x32`
+fn parse_pair(text: &str) -> Result<(u32, u32), Box<dyn Error>> {
+	let (a, b) = text.split_once('x').ok_or(format!("expected two numbers in the form AxB, found `{}`", text))?;
+	Ok((a.trim().parse()?, b.trim().parse()?))
+}
@@ -1,4 +1,4 @@
-use crate::logic::tile::{Tile, TileModifier, TileState};
+use crate::logic::{tile::Tile, visible::VisibleTile};
 
 #[repr(usize)]
 pub enum TileIndex {
@@ -6,7 +6,8 @@ pub enum TileIndex {
 	Revealed,
 	Flag,
 	Question,
-	RevealedQuestion, //UNUSED. I'm not sure how this can come to be given that the win condition is for all empty tiles to be revealed.
+	// A question mark held down, which also stands in for impossible numbers
+	RevealedQuestion,
 	RevealedMine,
 	Explosion,
 	FalseFlagMine,
@@ -21,46 +22,40 @@ pub enum TileIndex {
 }
 
 impl Tile {
-	pub fn render(self, game_over: bool) -> TileIndex {
-		// Behold: the match statement from hell!
-		match (
-			self.state,
-			self.modifier,
-			self.adjacent,
-			game_over,
-			self.swept,
-			self.highlighted,
-		) {
-			// Has mine, clicked mine: BOOM!
-			(TileState::Mine, _, _, _, true, _) => TileIndex::Explosion,
+	// `mine` says whether the tile holds a mine, which is only given once the game is over and the mines are shown.
+	pub fn render(self, mine: Option<bool>) -> TileIndex {
+		match (self.visible(), mine, self.highlighted) {
+			// Clicked mine: BOOM!
+			(VisibleTile::Exploded, _, _) => TileIndex::Explosion,
 			// Has mine, has flag, and game is over: True Flag
-			(TileState::Mine, Some(TileModifier::Flagged), _, true, _, _) => TileIndex::Flag,
+			(VisibleTile::Flagged, Some(true), _) => TileIndex::Flag,
 			// Has flag, is not Mine, and game is over: False flag
-			(TileState::Empty, Some(TileModifier::Flagged), _, true, _, _) => TileIndex::FalseFlagMine,
+			(VisibleTile::Flagged, Some(false), _) => TileIndex::FalseFlagMine,
 			// Revealed mine after game is over
-			(TileState::Mine, _, _, true, _, _) => TileIndex::RevealedMine,
+			(VisibleTile::Hidden | VisibleTile::Questioned, Some(true), _) => TileIndex::RevealedMine,
 			// Revealed tiles with adjacent tile count
-			(TileState::Empty, _, 0, _, true, _) => TileIndex::Revealed,
-			(TileState::Empty, _, 1, _, true, _) => TileIndex::One,
-			(TileState::Empty, _, 2, _, true, _) => TileIndex::Two,
-			(TileState::Empty, _, 3, _, true, _) => TileIndex::Three,
-			(TileState::Empty, _, 4, _, true, _) => TileIndex::Four,
-			(TileState::Empty, _, 5, _, true, _) => TileIndex::Five,
-			(TileState::Empty, _, 6, _, true, _) => TileIndex::Six,
-			(TileState::Empty, _, 7, _, true, _) => TileIndex::Seven,
-			(TileState::Empty, _, 8, _, true, _) => TileIndex::Eight,
+			(VisibleTile::Revealed(0), _, _) => TileIndex::Revealed,
+			(VisibleTile::Revealed(1), _, _) => TileIndex::One,
+			(VisibleTile::Revealed(2), _, _) => TileIndex::Two,
+			(VisibleTile::Revealed(3), _, _) => TileIndex::Three,
+			(VisibleTile::Revealed(4), _, _) => TileIndex::Four,
+			(VisibleTile::Revealed(5), _, _) => TileIndex::Five,
+			(VisibleTile::Revealed(6), _, _) => TileIndex::Six,
+			(VisibleTile::Revealed(7), _, _) => TileIndex::Seven,
+			(VisibleTile::Revealed(8), _, _) => TileIndex::Eight,
+			// unsigned 8 bit integer has too much range for the adjacent tiles count, creating an invalid state
+			// from 9 onward. This clause is to catch if somehow this invalid state occurs, and display
+			// the invalid tile in that case.
+			(VisibleTile::Revealed(9..=u8::MAX), _, _) => TileIndex::RevealedQuestion,
 			// Flag modifier
-			(_, Some(TileModifier::Flagged), _, _, _, _) => TileIndex::Flag,
+			(VisibleTile::Flagged, None, _) => TileIndex::Flag,
 			// Question mark modifier
-			(_, Some(TileModifier::Unsure), _, _, _, _) => TileIndex::Question,
+			(VisibleTile::Questioned, _, true) => TileIndex::RevealedQuestion,
+			(VisibleTile::Questioned, _, false) => TileIndex::Question,
 			// No modifier, not swept, but highlighted
-			(_, None, _, _, false, true) => TileIndex::Revealed,
+			(VisibleTile::Hidden, _, true) => TileIndex::Revealed,
 			// No modifier, Not swept, and not highlighted: Unknown tile
-			(_, None, _, _, false, false) => TileIndex::Unknown,
-			// unsigned 8 bit integer has too much range for the adjacent tiles count, creating an invalid state
-			// from 9 onward. This last clause is to catch if somehow this invalid state occurs, and display
-			// the invalid tile in that case.
-			(TileState::Empty, _, 9..=u8::MAX, _, true, _) => TileIndex::RevealedQuestion,
+			(VisibleTile::Hidden, _, false) => TileIndex::Unknown,
 		}
 	}
 }
@@ -4,18 +4,27 @@ use macroquad::{
 };
 
 pub mod flag_counter;
+pub mod player_scores;
 pub mod smile;
 pub mod timer;
 
-use crate::{gui::GUIEvent, logic::Minesweeper, util::Events};
+use crate::{gui::GUIEvent, logic::hot_seat::HotSeat, util::Events};
 
-use self::{flag_counter::GUIFlagCounter, smile::GUISmile, timer::GUITimer};
+use self::{flag_counter::GUIFlagCounter, player_scores::GUIPlayerScores, smile::GUISmile, timer::GUITimer};
 
 use super::{texture_store::TextureStore, UIState};
 use macroquad::prelude::*;
+// What the counter left of the smiley shows
+pub enum Counter<'a> {
+	// Remaining flags, or the score of an endless game
+	Single(isize),
+	Players(&'a HotSeat),
+}
+
 #[derive(Default)]
 pub struct GUITop {
 	pub flag_counter: GUIFlagCounter,
+	pub player_scores: GUIPlayerScores,
 	pub timer: GUITimer,
 	pub smile: GUISmile,
 }
@@ -24,7 +33,8 @@ impl GUITop {
 	pub fn render(
 		&mut self,
 		ui_state: &UIState,
-		game_logic: &Minesweeper,
+		time: Option<f64>,
+		counter: Counter,
 		event_handler: &mut Events<GUIEvent>,
 		textures: &TextureStore,
 	) {
@@ -69,9 +79,12 @@ impl GUITop {
 				}
 			}
 
-			self.timer.render(ui_state, game_logic.get_time(), ui, textures);
+			self.timer.render(ui_state, time, ui, textures);
 			self.smile.render(ui_state, ui, event_handler, textures);
-			self.flag_counter.render(ui_state, game_logic.board.remaining_flags(), ui, textures);
+			match counter {
+				Counter::Single(count) => self.flag_counter.render(ui_state, count, ui, textures),
+				Counter::Players(hot_seat) => self.player_scores.render(ui_state, hot_seat, ui, textures),
+			}
 		});
 	}
 }
@@ -0,0 +1,66 @@
+use macroquad::{prelude::*, ui::Ui};
+
+use crate::{
+	gui::{
+		seven_segment::{self, draw_small_seven_segment},
+		texture_store::TextureStore,
+	},
+	logic::hot_seat::HotSeat,
+};
+
+use super::UIState;
+
+// Each player's colour, used to mark their score and whose turn it is
+pub const PLAYER_COLORS: [Color; 4] = [
+	Color::new(0.85, 0.1, 0.1, 1.0),
+	Color::new(0.1, 0.25, 0.85, 1.0),
+	Color::new(0.1, 0.6, 0.1, 1.0),
+	Color::new(0.9, 0.6, 0.0, 1.0),
+];
+const DIGIT_WIDTH: usize = seven_segment::WIDTH / 2;
+const DIGIT_HEIGHT: usize = seven_segment::HEIGHT / 2;
+// A sign and three digits
+const SCORE_DIGITS: usize = 4;
+const COLUMN_GAP: usize = 12;
+const ROW_GAP: usize = 8;
+// Width of the frame around the score of the player whose turn it is
+const FRAME: usize = 3;
+// Symbol shown in place of the digits of players who are out
+const DASH: usize = 10;
+
+// Scores of a hot-seat game, in a grid of two columns where the flag counter normally is.
+#[derive(Default)]
+pub struct GUIPlayerScores;
+
+impl GUIPlayerScores {
+	pub fn render(&mut self, ui_state: &UIState, hot_seat: &HotSeat, ui: &mut Ui, textures: &TextureStore) {
+		let score_width = DIGIT_WIDTH * SCORE_DIGITS;
+		let top_y = ui_state.top_offset / 2 - DIGIT_HEIGHT - ROW_GAP / 2;
+		for (n, player) in hot_seat.players.iter().enumerate() {
+			let x = seven_segment::WIDTH * 2 + (score_width + COLUMN_GAP) * (n % 2);
+			let y = top_y + (DIGIT_HEIGHT + ROW_GAP) * (n / 2);
+
+			// Drawn shapes end up behind the digits, which are ui widgets
+			let color = PLAYER_COLORS[n % PLAYER_COLORS.len()];
+			if n == hot_seat.current {
+				let (frame_x, frame_y) = ui_state.pixel_screen_offset(x - FRAME, y - FRAME);
+				let (frame_width, frame_height) = ui_state.pixel_screen_scale(score_width + FRAME * 2, DIGIT_HEIGHT + FRAME * 2);
+				draw_rectangle(frame_x, frame_y, frame_width, frame_height, color);
+			} else {
+				let (bar_x, bar_y) = ui_state.pixel_screen_offset(x, y + DIGIT_HEIGHT);
+				let (bar_width, bar_height) = ui_state.pixel_screen_scale(score_width, FRAME);
+				draw_rectangle(bar_x, bar_y, bar_width, bar_height, color);
+			}
+
+			let digits = if player.eliminated {
+				vec![DASH; SCORE_DIGITS]
+			} else {
+				let score = player.score.clamp(-999, 999);
+				let mut digits = vec![if score < 0 { DASH } else { 0 }];
+				digits.extend(format!("{:0>3}", score.abs()).chars().map(|digit| digit.to_digit(10).unwrap_or(0) as usize));
+				digits
+			};
+			draw_small_seven_segment(ui_state, ui, textures, &digits, x, y);
+		}
+	}
+}
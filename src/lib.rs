@@ -0,0 +1,9 @@
+// Everything but the window's main loop, shared by the game and the relay binaries and by the integration tests
+pub mod agent;
+pub mod cli;
+pub mod gui;
+pub mod logic;
+pub mod net;
+pub mod snapshot;
+pub mod sprite_loader;
+pub mod util;
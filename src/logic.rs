@@ -1,12 +1,27 @@
 // pub mod events;
+pub mod analysis;
+mod chunk_store;
+pub mod endless;
+pub mod event_log;
+pub mod first_click;
 pub mod game_board;
+pub mod hot_seat;
+pub mod preset;
+pub mod replay;
 pub mod tile;
 mod timer;
-use crate::util::Events;
+pub mod visible;
+use crate::util::{Events, Subscriber, ADJACENT_WITH_CENTER};
 
-use self::{tile::Tile, timer::Timer};
-use game_board::GameBoard;
-use std::error::Error;
+use self::{
+	first_click::FirstClick,
+	hot_seat::HotSeat,
+	replay::{Replay, ReplayAction, ReplayEvent},
+	tile::Tile,
+	timer::Timer,
+};
+use game_board::{GameBoard, ModifyMode};
+use std::{error::Error, fmt};
 
 #[derive(PartialEq, Default)]
 pub enum GameState {
@@ -18,15 +33,37 @@ pub enum GameState {
 }
 pub enum GameEvent {
 	Lose(usize, usize, Tile),
+	// A mine was hit in a hot-seat game, which carries on without the player who hit it or with fewer points
+	Detonate(usize, usize, Tile),
 	RevealTile(usize, usize, Tile),
 	FlagTile(usize, usize, Tile),
 	QuestionTile(usize, usize, Tile),
+	Chord(usize, usize),
 	SweepDone,
 	SweepBegin,
 	InitDone,
 	Win,
 	Reset,
-	GameEnd(GameBoard),
+	GameEnd(Box<GameBoard>),
+}
+
+impl fmt::Display for GameEvent {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			GameEvent::Lose(x, y, tile) => write!(f, "lose {} {} {}", x, y, tile),
+			GameEvent::Detonate(x, y, tile) => write!(f, "detonate {} {} {}", x, y, tile),
+			GameEvent::RevealTile(x, y, tile) => write!(f, "reveal {} {} {}", x, y, tile),
+			GameEvent::FlagTile(x, y, tile) => write!(f, "flag {} {} {}", x, y, tile),
+			GameEvent::QuestionTile(x, y, tile) => write!(f, "question {} {} {}", x, y, tile),
+			GameEvent::Chord(x, y) => write!(f, "chord {} {}", x, y),
+			GameEvent::SweepDone => write!(f, "sweep_done"),
+			GameEvent::SweepBegin => write!(f, "sweep_begin"),
+			GameEvent::InitDone => write!(f, "init_done"),
+			GameEvent::Win => write!(f, "win"),
+			GameEvent::Reset => write!(f, "reset"),
+			GameEvent::GameEnd(board) => write!(f, "game_end {}x{}", board.width(), board.height()),
+		}
+	}
 }
 
 #[derive(Default)]
@@ -35,12 +72,15 @@ pub struct Minesweeper {
 	pub events: Events<GameEvent>,
 	pub state: GameState,
 	timer: Timer,
+	actions: Vec<ReplayEvent>,
+	// Set when several players share the board, taking turns
+	pub hot_seat: Option<HotSeat>,
 }
 
 impl Minesweeper {
-	pub fn new(width: usize, height: usize, mines: usize) -> Result<Minesweeper, Box<dyn Error>> {
+	pub fn new(width: usize, height: usize, mines: usize, first_click: FirstClick) -> Result<Minesweeper, Box<dyn Error>> {
 		let game = Self {
-			board: GameBoard::new(width, height, mines)?,
+			board: GameBoard::new(width, height, mines, first_click)?,
 			..Default::default()
 		};
 
@@ -52,9 +92,32 @@ impl Minesweeper {
 		self.events.clear();
 		self.events.add(GameEvent::Reset);
 		self.timer.clear();
+		self.actions.clear();
+		if let Some(hot_seat) = &mut self.hot_seat {
+			hot_seat.reset();
+		}
+	}
+	// Switches between a normal game and a hot-seat one, starting a new game.
+	pub fn set_hot_seat(&mut self, hot_seat: Option<HotSeat>) {
+		self.hot_seat = hot_seat;
+		self.reset();
 	}
-	pub fn update_and_reset(&mut self, width: usize, height: usize, mines: usize) {
-		self.board.update(width, height, mines);
+	pub fn update_and_reset(&mut self, width: usize, height: usize, mines: usize) -> Result<(), Box<dyn Error>> {
+		self.board.update(width, height, mines)?;
+		self.reset();
+		Ok(())
+	}
+	// Starts a new game under the rule, if the board has room for it
+	pub fn set_first_click(&mut self, first_click: FirstClick) -> Result<(), Box<dyn Error>> {
+		self.board.set_first_click(first_click)?;
+		self.reset();
+		Ok(())
+	}
+	pub fn load_board(&mut self, mut board: GameBoard) {
+		board.modify_mode = self.board.modify_mode.clone();
+		board.flag_rules = self.board.flag_rules;
+		board.first_click = self.board.first_click;
+		self.board = board;
 		self.reset();
 	}
 
@@ -64,26 +127,172 @@ impl Minesweeper {
 			self.state = GameState::Playing;
 			self.events.add(GameEvent::InitDone);
 		}
+		if self.state != GameState::Playing || !self.board.is_valid_coord(x, y) {
+			return;
+		}
+		self.record(ReplayAction::Reveal, x, y);
+		let events_before = self.events.len();
+		self.sweep(x, y);
+		self.flag_obvious(events_before);
+		self.end_turn();
+	}
+	pub fn modify(&mut self, x: usize, y: usize) {
+		self.change_marker(x, y, GameBoard::modify);
+	}
+	pub fn question(&mut self, x: usize, y: usize) {
+		self.change_marker(x, y, GameBoard::question);
+	}
+	// Replays only have right clicks, so a change is recorded as the right clicks which make it
+	fn change_marker(&mut self, x: usize, y: usize, change: fn(&mut GameBoard, usize, usize, &mut Events<GameEvent>)) {
+		if self.state != GameState::Playing || !self.board.is_valid_coord(x, y) {
+			return;
+		}
+		if self.hot_seat.as_ref().is_some_and(|hot_seat| !hot_seat.may_modify(x, y)) {
+			return;
+		}
+		let modifier = |board: &GameBoard| board.get_tile(x, y).and_then(|tile| tile.modifier);
+		let before = modifier(&self.board);
+		let events_before = self.events.len();
+		change(&mut self.board, x, y, &mut self.events);
+		for _ in 0..self.board.modify_mode.right_clicks(before, modifier(&self.board)) {
+			self.record(ReplayAction::Modify, x, y);
+		}
+		self.score_events(events_before);
+	}
+	pub fn chord(&mut self, x: usize, y: usize) {
 		if self.state != GameState::Playing {
 			return;
 		}
-		if let Some(state) = self.board.sweep(x, y, &mut self.events) {
+		let targets = self.board.chord_targets(x, y);
+		if targets.is_empty() {
+			return;
+		}
+		self.record(ReplayAction::Chord, x, y);
+		let events_before = self.events.len();
+		self.events.add(GameEvent::Chord(x, y));
+		for (x, y) in targets {
+			// A player knocked out by a mine doesn't get to finish their chord
+			let eliminated = self.hot_seat.as_ref().is_some_and(|hot_seat| hot_seat.players[hot_seat.current].eliminated);
+			if self.state != GameState::Playing || eliminated {
+				break;
+			}
+			self.sweep(x, y);
+		}
+		self.flag_obvious(events_before);
+		self.end_turn();
+	}
+	fn sweep(&mut self, x: usize, y: usize) {
+		let events_before = self.events.len();
+		// In a hot-seat game a mine only ends the game for everyone when the last player standing hits it
+		let survivable = self.hot_seat.as_ref().is_some_and(HotSeat::survives_mine);
+		if survivable && self.board.is_hidden_mine(x, y) {
+			self.board.detonate(x, y, &mut self.events);
+		} else if let Some(state) = self.board.sweep(x, y, &mut self.events) {
 			if state == GameState::GameOver || state == GameState::Victory {
 				self.timer.stop()
 			}
 			self.state = state;
 		};
 		self.events.add(GameEvent::SweepDone);
+		self.score_events(events_before);
 	}
-	pub fn modify(&mut self, x: usize, y: usize) {
-		if self.state != GameState::Playing {
+	// Flags the mines made obvious by the tiles revealed in the events added since `from`, if the flag rules say to.
+	// The flags go straight onto the board, so in hot-seat games they belong to no one and aren't scored. Replays
+	// hold them as the right clicks which would place them, which is why `ReplayPlayer` turns this rule off.
+	fn flag_obvious(&mut self, from: usize) {
+		if !self.board.flag_rules.flag_obvious || self.state != GameState::Playing {
 			return;
 		}
-		self.board.modify(x, y, &mut self.events)
+		// A reveal changes what's unrevealed around every number next to it
+		let mut numbers = vec![];
+		for event in self.events.iter().skip(from) {
+			if let GameEvent::RevealTile(x, y, _) = *event {
+				for (offset_x, offset_y) in ADJACENT_WITH_CENTER {
+					let (Some(x), Some(y)) = (x.checked_add_signed(offset_x), y.checked_add_signed(offset_y)) else {
+						continue;
+					};
+					numbers.push((x, y));
+				}
+			}
+		}
+		numbers.sort_unstable();
+		numbers.dedup();
+		let mut mines: Vec<(usize, usize)> = numbers.into_iter().flat_map(|(x, y)| self.board.obvious_mines(x, y)).collect();
+		mines.sort_unstable();
+		mines.dedup();
+		let modifier = |board: &GameBoard, x: usize, y: usize| board.get_tile(x, y).and_then(|tile| tile.modifier);
+		for (x, y) in mines {
+			let before = modifier(&self.board, x, y);
+			self.board.flag(x, y, &mut self.events);
+			for _ in 0..self.board.modify_mode.right_clicks(before, modifier(&self.board, x, y)) {
+				self.record(ReplayAction::Modify, x, y);
+			}
+		}
+	}
+	// Hands the events added since `from` to the hot-seat scores, if players are taking turns.
+	fn score_events(&mut self, from: usize) {
+		if let Some(hot_seat) = &mut self.hot_seat {
+			for event in self.events.iter().skip(from) {
+				hot_seat.notify(event);
+			}
+		}
+	}
+	fn end_turn(&mut self) {
+		if let Some(hot_seat) = &mut self.hot_seat {
+			hot_seat.end_turn();
+		}
+	}
+	fn record(&mut self, action: ReplayAction, x: usize, y: usize) {
+		let time = self.get_time().unwrap_or_default();
+		self.actions.push(ReplayEvent { time, action, x, y });
+	}
+	// The game played so far, or None if no tile has been revealed yet. Hot-seat games can't be replayed.
+	pub fn replay(&self) -> Option<Replay> {
+		if self.state == GameState::Empty || self.hot_seat.is_some() {
+			return None;
+		}
+		Some(Replay {
+			board: self.board.layout(),
+			events: self.actions.clone(),
+			question_marks: !matches!(self.board.modify_mode, ModifyMode::Flag),
+			first_click: Some(self.board.first_click),
+			time: self.get_time(),
+		})
 	}
 	pub fn get_time(&self) -> Option<f64> {
 		self.timer.elapsed()
 	}
+	// Follows a game played elsewhere, as the clients of a shared board do. Tiles change separately through
+	// `GameBoard::mirror_tile`; this keeps the state and timer in step and passes the event on to subscribers.
+	pub fn mirror(&mut self, event: GameEvent) {
+		match event {
+			GameEvent::RevealTile(_, _, _) if self.state == GameState::Empty => {
+				self.timer.start();
+				self.state = GameState::Playing;
+			}
+			GameEvent::Lose(_, _, _) => {
+				self.timer.stop();
+				self.state = GameState::GameOver;
+				self.board.end();
+			}
+			GameEvent::Win => {
+				self.timer.stop();
+				self.state = GameState::Victory;
+				self.board.end();
+			}
+			GameEvent::Reset => {
+				self.board.reset();
+				self.timer.clear();
+				self.state = GameState::Empty;
+			}
+			_ => (),
+		}
+		self.events.add(event);
+	}
+	// Sets the timer of a mirrored game to the time of the game it follows
+	pub fn mirror_time(&mut self, elapsed: f64) {
+		self.timer.set(elapsed, self.state == GameState::Playing);
+	}
 	pub fn highlight(&mut self, x: usize, y: usize) {
 		if self.state == GameState::Playing || self.state == GameState::Empty {
 			if let Some(tile) = self.board.get_tile_mut(x, y) {
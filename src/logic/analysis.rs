@@ -0,0 +1,207 @@
+use std::{error::Error, fmt};
+
+use crate::agent::solver;
+
+use super::{first_click::FirstClick, Minesweeper};
+
+// Rows in the 3BV histogram, at most
+const HISTOGRAM_ROWS: usize = 16;
+// Width of the longest histogram bar, in characters
+const HISTOGRAM_WIDTH: usize = 50;
+
+// Statistics over many generated boards, to check that generation gives the spread of boards it should. Each board
+// is generated for a first click in the centre, from consecutive seeds.
+pub struct Analysis {
+	pub board: (usize, usize, usize),
+	pub first_click: FirstClick,
+	pub first_seed: u64,
+	pub bbbv: Vec<usize>,
+	pub openings: Vec<usize>,
+	// Numbers not on the edge of an opening, each needing a click of its own
+	pub isolated_numbers: Vec<usize>,
+	// Boards which can't be cleared from the first click without a guess
+	pub needs_guess: usize,
+	// How many boards had a mine on each tile, row by row
+	pub mine_counts: Vec<usize>,
+}
+
+pub fn analyze(
+	board: (usize, usize, usize),
+	first_click: FirstClick,
+	boards: usize,
+	seed: u64,
+) -> Result<Analysis, Box<dyn Error>> {
+	let (width, height, mines) = board;
+	let mut analysis = Analysis {
+		board,
+		first_click,
+		first_seed: seed,
+		bbbv: Vec::with_capacity(boards),
+		openings: Vec::with_capacity(boards),
+		isolated_numbers: Vec::with_capacity(boards),
+		needs_guess: 0,
+		mine_counts: vec![0; width * height],
+	};
+	for n in 0..boards as u64 {
+		let mut game = Minesweeper::new(width, height, mines, first_click)?;
+		game.board.seed = Some(seed.wrapping_add(n));
+		game.reveal(width / 2, height / 2);
+		let layout = game.board.layout();
+		let bbbv = layout.bbbv();
+		let openings = layout.openings().len();
+		analysis.bbbv.push(bbbv);
+		analysis.openings.push(openings);
+		analysis.isolated_numbers.push(bbbv - openings);
+		for y in 0..height {
+			for x in 0..width {
+				if layout.is_mine(x, y) {
+					analysis.mine_counts[y * width + x] += 1;
+				}
+			}
+		}
+		if !solver::play_safe(&mut game) {
+			analysis.needs_guess += 1;
+		}
+	}
+	Ok(analysis)
+}
+
+impl Analysis {
+	// Tiles which can never have a mine, leaving the mines moved out of the way of the first click to show up
+	fn is_safe_zone(&self, x: usize, y: usize) -> bool {
+		let (width, height, _) = self.board;
+		let radius = match self.first_click {
+			FirstClick::Safe => Some(0),
+			first_click => first_click.clear_radius(),
+		};
+		radius.is_some_and(|radius| x.abs_diff(width / 2) <= radius && y.abs_diff(height / 2) <= radius)
+	}
+
+	// Pearson's statistic for every tile outside the safe zone being equally likely to hold a mine, and its degrees of
+	// freedom. As each board has exactly its number of mines, tiles are measured against the variance of drawing
+	// without replacement, which scales the usual statistic so it still follows the chi-square distribution.
+	pub fn chi_square(&self) -> (f64, usize) {
+		let (width, _, mines) = self.board;
+		let counts: Vec<usize> = (self.mine_counts.iter().enumerate())
+			.filter(|(i, _)| !self.is_safe_zone(i % width, i / width))
+			.map(|(_, &count)| count)
+			.collect();
+		let tiles = counts.len() as f64;
+		let chance = mines as f64 / tiles;
+		let expected = self.bbbv.len() as f64 * chance;
+		let variance = expected * (1.0 - chance);
+		if counts.len() < 2 || variance <= 0.0 {
+			return (0.0, 0);
+		}
+		let sum: f64 = counts.iter().map(|&count| (count as f64 - expected).powi(2) / variance).sum();
+		(sum * (tiles - 1.0) / tiles, counts.len() - 1)
+	}
+}
+
+// Chance of a chi-square statistic at least this large if the mines are uniform, by the Wilson–Hilferty
+// approximation, which is close for the hundreds of degrees of freedom a board has
+fn chi_square_p_value(statistic: f64, degrees: usize) -> f64 {
+	let degrees = degrees as f64;
+	let spread = 2.0 / (9.0 * degrees);
+	let z = ((statistic / degrees).cbrt() - (1.0 - spread)) / spread.sqrt();
+	0.5 * erfc(z / std::f64::consts::SQRT_2)
+}
+
+// Complementary error function, to within about 1e-7 (Numerical Recipes' erfcc)
+fn erfc(x: f64) -> f64 {
+	let t = 1.0 / (1.0 + 0.5 * x.abs());
+	let polynomial = [
+		-1.26551223,
+		1.00002368,
+		0.37409196,
+		0.09678418,
+		-0.18628806,
+		0.27886807,
+		-1.13520398,
+		1.48851587,
+		-0.82215223,
+		0.17087277,
+	]
+	.iter()
+	.rev()
+	.fold(0.0, |sum, coefficient| sum * t + coefficient);
+	let result = t * (-x * x + polynomial).exp();
+	if x >= 0.0 {
+		result
+	} else {
+		2.0 - result
+	}
+}
+
+fn mean(values: &[usize]) -> f64 {
+	values.iter().sum::<usize>() as f64 / values.len().max(1) as f64
+}
+
+fn summary(values: &[usize]) -> String {
+	format!(
+		"{:.1} per board (min {}, max {})",
+		mean(values),
+		values.iter().min().unwrap_or(&0),
+		values.iter().max().unwrap_or(&0)
+	)
+}
+
+impl fmt::Display for Analysis {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let (width, height, mines) = self.board;
+		let boards = self.bbbv.len();
+		writeln!(
+			f,
+			"{} boards of {}x{} with {} mines, first click rule '{}', seeds {} to {}, first click at {}, {}",
+			boards,
+			width,
+			height,
+			mines,
+			self.first_click.name(),
+			self.first_seed,
+			self.first_seed.wrapping_add(boards.saturating_sub(1) as u64),
+			width / 2,
+			height / 2
+		)?;
+		writeln!(f, "3BV:              {}", summary(&self.bbbv))?;
+		writeln!(f, "openings:         {}", summary(&self.openings))?;
+		writeln!(f, "isolated numbers: {}", summary(&self.isolated_numbers))?;
+		writeln!(
+			f,
+			"needs a guess:    {} ({:.1}%)",
+			self.needs_guess,
+			self.needs_guess as f64 / boards.max(1) as f64 * 100.0
+		)?;
+		let (statistic, degrees) = self.chi_square();
+		writeln!(
+			f,
+			"mine placement:   chi-square {:.1} over {} degrees of freedom, p = {:.3}",
+			statistic,
+			degrees,
+			chi_square_p_value(statistic, degrees)
+		)?;
+
+		// Buckets are as wide as they need to be to fit the spread in the rows there are
+		let (Some(&lowest), Some(&highest)) = (self.bbbv.iter().min(), self.bbbv.iter().max()) else {
+			return Ok(());
+		};
+		let bucket_width = (highest - lowest + 1).div_ceil(HISTOGRAM_ROWS);
+		let mut buckets = vec![0; (highest - lowest) / bucket_width + 1];
+		for bbbv in &self.bbbv {
+			buckets[(bbbv - lowest) / bucket_width] += 1;
+		}
+		let most = buckets.iter().copied().max().unwrap_or(1);
+		write!(f, "3BV histogram:")?;
+		for (n, &count) in buckets.iter().enumerate() {
+			let start = lowest + n * bucket_width;
+			let range = if bucket_width == 1 {
+				start.to_string()
+			} else {
+				format!("{}-{}", start, start + bucket_width - 1)
+			};
+			let bar = "#".repeat((count * HISTOGRAM_WIDTH).div_ceil(most));
+			write!(f, "\n{:>9} {:>6} {}", range, count, bar)?;
+		}
+		Ok(())
+	}
+}
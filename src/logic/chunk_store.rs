@@ -0,0 +1,218 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::util::{ADJACENT_WITHOUT_CENTER, ADJACENT_WITH_CENTER};
+
+use super::tile::{Tile, TileModifier, TileState};
+
+// Boards are stored in square chunks of this many tiles a side
+pub const CHUNK_SIZE: usize = 32;
+const CHUNK_AREA: usize = CHUNK_SIZE * CHUNK_SIZE;
+
+// What a tile which has never been touched looks like
+static UNTOUCHED_TILE: Tile = Tile {
+	state: TileState::Empty,
+	modifier: None,
+	swept: false,
+	adjacent: 0,
+	safe: false,
+	highlighted: false,
+};
+
+#[derive(Clone)]
+pub struct Chunk {
+	pub tiles: Vec<Tile>,
+	// Set once the chunk's mines have been placed
+	pub mined: bool,
+	// Set once the adjacent mine counts of the chunk's tiles are known, which needs the mines of the
+	// surrounding chunks too.
+	pub ready: bool,
+}
+
+impl Default for Chunk {
+	fn default() -> Self {
+		Self {
+			tiles: vec![Tile::default(); CHUNK_AREA],
+			mined: false,
+			ready: false,
+		}
+	}
+}
+
+// Where a board's mines go. Boards with and without edges place them differently, but lay out their chunks,
+// flood fill and chord the same way through `ChunkStore`.
+pub trait MinePlacer {
+	// Whether the tile is part of the board, which every tile is when the board has no edges
+	fn contains(&self, x: i64, y: i64) -> bool;
+	// Marks the mines and the tiles kept safe in a chunk, which has already been created in `tiles`
+	fn place_mines(&self, chunk_x: i64, chunk_y: i64, tiles: &mut ChunkStore);
+}
+
+// Sparse tile storage. Chunks only exist once something has happened in them, so a huge board costs
+// nothing until it is played, and clearing it is instant. Coordinates are signed so that boards
+// without edges can extend in every direction.
+#[derive(Clone, Default)]
+pub struct ChunkStore {
+	chunks: HashMap<(i64, i64), Chunk>,
+}
+
+impl ChunkStore {
+	pub fn chunk_of(x: i64, y: i64) -> (i64, i64) {
+		(x.div_euclid(CHUNK_SIZE as i64), y.div_euclid(CHUNK_SIZE as i64))
+	}
+
+	fn index_in_chunk(x: i64, y: i64) -> usize {
+		let size = CHUNK_SIZE as i64;
+		(y.rem_euclid(size) * size + x.rem_euclid(size)) as usize
+	}
+
+	// The tile at `x`, `y`, which looks untouched if its chunk doesn't exist yet
+	pub fn get(&self, x: i64, y: i64) -> &Tile {
+		match self.chunks.get(&Self::chunk_of(x, y)) {
+			Some(chunk) => &chunk.tiles[Self::index_in_chunk(x, y)],
+			None => &UNTOUCHED_TILE,
+		}
+	}
+
+	// The tile at `x`, `y`. The chunk must already exist.
+	pub fn get_mut(&mut self, x: i64, y: i64) -> Option<&mut Tile> {
+		let chunk = self.chunks.get_mut(&Self::chunk_of(x, y))?;
+		Some(&mut chunk.tiles[Self::index_in_chunk(x, y)])
+	}
+
+	pub fn chunk(&self, chunk_x: i64, chunk_y: i64) -> Option<&Chunk> {
+		self.chunks.get(&(chunk_x, chunk_y))
+	}
+
+	// Creates the chunk if it doesn't exist yet
+	pub fn chunk_mut(&mut self, chunk_x: i64, chunk_y: i64) -> &mut Chunk {
+		self.chunks.entry((chunk_x, chunk_y)).or_default()
+	}
+
+	pub fn chunks_mut(&mut self) -> impl Iterator<Item = &mut Chunk> {
+		self.chunks.values_mut()
+	}
+
+	pub fn clear(&mut self) {
+		self.chunks.clear();
+	}
+
+	// Makes sure a chunk's tiles know their mines and adjacent counts, placing the mines of the chunk and the chunks
+	// around it first if they don't have them yet.
+	pub fn prepare_chunk(&mut self, chunk_x: i64, chunk_y: i64, placer: &impl MinePlacer) {
+		if self.chunk(chunk_x, chunk_y).is_some_and(|chunk| chunk.ready) {
+			return;
+		}
+		// Tiles on the edge of the chunk count mines in the chunks around it
+		for &(offset_x, offset_y) in ADJACENT_WITH_CENTER.iter() {
+			let (x, y) = (chunk_x + offset_x as i64, chunk_y + offset_y as i64);
+			let size = CHUNK_SIZE as i64;
+			if !placer.contains(x * size, y * size) || self.chunk(x, y).is_some_and(|chunk| chunk.mined) {
+				continue;
+			}
+			self.chunk_mut(x, y).mined = true;
+			placer.place_mines(x, y, self);
+		}
+		let counts = self.adjacent_counts(chunk_x, chunk_y);
+		let chunk = self.chunk_mut(chunk_x, chunk_y);
+		for (tile, count) in chunk.tiles.iter_mut().zip(counts) {
+			tile.adjacent = count;
+		}
+		chunk.ready = true;
+	}
+
+	// The tile at `x`, `y`, preparing its chunk first if needed so the tile is complete
+	pub fn prepared_mut(&mut self, x: i64, y: i64, placer: &impl MinePlacer) -> &mut Tile {
+		let (chunk_x, chunk_y) = Self::chunk_of(x, y);
+		self.prepare_chunk(chunk_x, chunk_y, placer);
+		self.get_mut(x, y).expect("Chunk was just prepared")
+	}
+
+	// Hidden, unflagged tiles around a revealed number whose flag count matches it, which are the tiles a chord
+	// reveals. Mines which have been set off count as flagged.
+	pub fn chord_targets(&self, x: i64, y: i64, placer: &impl MinePlacer) -> Vec<(i64, i64)> {
+		let tile = self.get(x, y);
+		if !tile.swept || tile.adjacent == 0 {
+			return vec![];
+		}
+		let mut adjacent_flags = 0;
+		let mut targets = vec![];
+		for &(offset_x, offset_y) in ADJACENT_WITHOUT_CENTER.iter() {
+			let (x, y) = (x + offset_x as i64, y + offset_y as i64);
+			if !placer.contains(x, y) {
+				continue;
+			}
+			let neighbour = self.get(x, y);
+			if neighbour.modifier == Some(TileModifier::Flagged) || (neighbour.swept && neighbour.state == TileState::Mine) {
+				adjacent_flags += 1;
+			} else if !neighbour.swept {
+				targets.push((x, y));
+			}
+		}
+		if adjacent_flags != tile.adjacent {
+			return vec![];
+		}
+		targets
+	}
+
+	// Reveals the tiles around the revealed tile at `x`, `y` if it has no adjacent mines, carrying on through every
+	// such tile it reaches. Breadth first, so tiles are revealed in rings around where it began. Flags and question
+	// marks on the revealed tiles are taken off. Gives the revealed tiles in order, and how many flags came off.
+	pub fn flood(&mut self, x: i64, y: i64, placer: &impl MinePlacer) -> (Vec<(i64, i64)>, usize) {
+		let mut revealed = vec![];
+		let mut flags = 0;
+		let mut scan_list = VecDeque::from([(x, y)]);
+		while let Some((x, y)) = scan_list.pop_front() {
+			if self.get(x, y).adjacent > 0 {
+				continue;
+			}
+			for &(offset_x, offset_y) in ADJACENT_WITHOUT_CENTER.iter() {
+				let (x, y) = (x + offset_x as i64, y + offset_y as i64);
+				if !placer.contains(x, y) {
+					continue;
+				}
+				let tile = self.prepared_mut(x, y, placer);
+				if tile.swept {
+					continue;
+				}
+				tile.swept = true;
+				flags += (tile.modifier == Some(TileModifier::Flagged)) as usize;
+				tile.modifier = None;
+				scan_list.push_back((x, y));
+				revealed.push((x, y));
+			}
+		}
+		(revealed, flags)
+	}
+
+	// Adjacent mine counts of every tile in a chunk, in the chunk's tile order. Mines must already be placed
+	// in the chunk and the chunks around it. Looks the surrounding chunks up once rather than once per tile,
+	// as this runs for every tile of the board.
+	pub fn adjacent_counts(&self, chunk_x: i64, chunk_y: i64) -> Vec<u8> {
+		let mut neighbours = [None; 9];
+		for (neighbour, &(offset_x, offset_y)) in neighbours.iter_mut().zip(ADJACENT_WITH_CENTER.iter()) {
+			*neighbour = self.chunk(chunk_x + offset_x as i64, chunk_y + offset_y as i64);
+		}
+		// `x` and `y` are relative to the chunk's corner and may be up to one tile outside of it
+		let is_mine = |x: isize, y: isize| {
+			let size = CHUNK_SIZE as isize;
+			let neighbour = ((y + size) / size * 3 + (x + size) / size) as usize;
+			let index = (y.rem_euclid(size) * size + x.rem_euclid(size)) as usize;
+			neighbours[neighbour].is_some_and(|chunk| chunk.tiles[index].state == TileState::Mine)
+		};
+
+		let mut counts = vec![0; CHUNK_AREA];
+		for y in 0..CHUNK_SIZE as isize {
+			for x in 0..CHUNK_SIZE as isize {
+				if is_mine(x, y) {
+					continue;
+				}
+				let count = ADJACENT_WITHOUT_CENTER
+					.iter()
+					.filter(|&&(offset_x, offset_y)| is_mine(x + offset_x, y + offset_y))
+					.count();
+				counts[(y * CHUNK_SIZE as isize + x) as usize] = count as u8;
+			}
+		}
+		counts
+	}
+}
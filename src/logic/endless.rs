@@ -0,0 +1,185 @@
+use super::{
+	chunk_store::{ChunkStore, MinePlacer, CHUNK_SIZE},
+	tile::{Tile, TileModifier, TileState},
+	timer::Timer,
+	GameState,
+};
+use crate::util::{time_seed, Events, Rng};
+
+// Share of tiles which are mines
+pub const DEFAULT_DENSITY: f64 = 0.18;
+// With fewer mines, areas without adjacent mines join up into one endless area, so a reveal would never finish.
+const MIN_DENSITY: f64 = 0.12;
+const MAX_DENSITY: f64 = 0.5;
+// How far around a lost game mines are shown, in chunks
+const LOSS_REVEAL_RADIUS: i64 = 2;
+
+pub enum EndlessEvent {
+	// Where the reveal began, and how many tiles it cleared
+	Reveal(i64, i64, usize),
+	Flag(i64, i64),
+	Chord(i64, i64),
+	Lose(i64, i64),
+	Reset,
+}
+
+// Mines of an endless field, which only depend on the world seed and the chunk
+struct FieldMines {
+	seed: u64,
+	// Share of tiles which are mines
+	density: f64,
+}
+
+impl MinePlacer for FieldMines {
+	fn contains(&self, _x: i64, _y: i64) -> bool {
+		true
+	}
+
+	fn place_mines(&self, chunk_x: i64, chunk_y: i64, tiles: &mut ChunkStore) {
+		let mut rng = Rng::derive(self.seed, chunk_x as u64, chunk_y as u64);
+		for (i, tile) in tiles.chunk_mut(chunk_x, chunk_y).tiles.iter_mut().enumerate() {
+			let x = chunk_x * CHUNK_SIZE as i64 + (i % CHUNK_SIZE) as i64;
+			let y = chunk_y * CHUNK_SIZE as i64 + (i / CHUNK_SIZE) as i64;
+			// Every tile gets a draw, so safe tiles don't shift the mines of the tiles after them
+			let mine = rng.chance(self.density);
+			if x.abs() <= 1 && y.abs() <= 1 {
+				tile.safe = true;
+			} else if mine {
+				tile.state = TileState::Mine;
+			}
+		}
+	}
+}
+
+// A minefield without edges, which is generated a chunk at a time as it is explored. Mines only depend on the
+// world seed and the chunk, so the same seed always gives the same field. The 3x3 area around the origin is
+// always safe to start on. The score is the number of tiles cleared before hitting a mine.
+pub struct EndlessGame {
+	tiles: ChunkStore,
+	mines: FieldMines,
+	pub state: GameState,
+	pub events: Events<EndlessEvent>,
+	score: usize,
+	timer: Timer,
+}
+
+impl EndlessGame {
+	pub fn new(seed: u64, density: f64) -> Self {
+		let mut events = Events::default();
+		events.add(EndlessEvent::Reset);
+		Self {
+			tiles: ChunkStore::default(),
+			mines: FieldMines {
+				seed,
+				density: density.clamp(MIN_DENSITY, MAX_DENSITY),
+			},
+			state: GameState::Empty,
+			events,
+			score: 0,
+			timer: Timer::default(),
+		}
+	}
+
+	// A new field with a seed taken from the clock
+	pub fn random() -> Self {
+		Self::new(time_seed(), DEFAULT_DENSITY)
+	}
+
+	pub fn score(&self) -> usize {
+		self.score
+	}
+
+	pub fn get_time(&self) -> Option<f64> {
+		self.timer.elapsed()
+	}
+
+	// Tiles in parts of the field which haven't been explored yet are hidden, and may not know their mine yet.
+	pub fn get_tile(&self, x: i64, y: i64) -> &Tile {
+		self.tiles.get(x, y)
+	}
+
+	// Whether a tile holds a mine, once the game is over and the mines are shown. None while it is being played.
+	pub fn shown_mine(&self, x: i64, y: i64) -> Option<bool> {
+		(self.state == GameState::GameOver).then(|| self.get_tile(x, y).state == TileState::Mine)
+	}
+
+	pub fn reveal(&mut self, x: i64, y: i64) {
+		if self.state == GameState::Empty {
+			self.timer.start();
+			self.state = GameState::Playing;
+		}
+		if self.state != GameState::Playing {
+			return;
+		}
+		self.sweep(x, y);
+	}
+
+	pub fn modify(&mut self, x: i64, y: i64) {
+		if self.state != GameState::Playing {
+			return;
+		}
+		let tile = self.get_tile_mut(x, y);
+		if tile.swept {
+			return;
+		}
+		tile.modifier = match tile.modifier {
+			Some(_) => None,
+			None => Some(TileModifier::Flagged),
+		};
+		self.events.add(EndlessEvent::Flag(x, y));
+	}
+
+	pub fn chord(&mut self, x: i64, y: i64) {
+		if self.state != GameState::Playing {
+			return;
+		}
+		let targets = self.chord_targets(x, y);
+		if targets.is_empty() {
+			return;
+		}
+		self.events.add(EndlessEvent::Chord(x, y));
+		for (x, y) in targets {
+			if self.state != GameState::Playing {
+				break;
+			}
+			self.sweep(x, y);
+		}
+	}
+
+	// Hidden, unflagged tiles around a revealed number whose flag count matches it.
+	pub fn chord_targets(&self, x: i64, y: i64) -> Vec<(i64, i64)> {
+		self.tiles.chord_targets(x, y, &self.mines)
+	}
+
+	fn sweep(&mut self, x: i64, y: i64) {
+		let tile = self.get_tile_mut(x, y);
+		if tile.modifier.is_some() || tile.swept {
+			return;
+		}
+		tile.swept = true;
+		if tile.state == TileState::Mine {
+			self.state = GameState::GameOver;
+			self.timer.stop();
+			// Show the mines around where the game was lost
+			let (chunk_x, chunk_y) = ChunkStore::chunk_of(x, y);
+			for chunk_y in chunk_y - LOSS_REVEAL_RADIUS..=chunk_y + LOSS_REVEAL_RADIUS {
+				for chunk_x in chunk_x - LOSS_REVEAL_RADIUS..=chunk_x + LOSS_REVEAL_RADIUS {
+					self.tiles.prepare_chunk(chunk_x, chunk_y, &self.mines);
+				}
+			}
+			self.events.add(EndlessEvent::Lose(x, y));
+			return;
+		}
+
+		// The density limits make sure the flood fill ends
+		let (revealed, _) = self.tiles.flood(x, y, &self.mines);
+		let revealed = revealed.len() + 1;
+		self.score += revealed;
+		self.events.add(EndlessEvent::Reveal(x, y, revealed));
+	}
+
+	// Generates the tile's part of the field first if needed, so the tile is complete.
+	fn get_tile_mut(&mut self, x: i64, y: i64) -> &mut Tile {
+		self.tiles.prepared_mut(x, y, &self.mines)
+	}
+}
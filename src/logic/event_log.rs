@@ -0,0 +1,22 @@
+use crate::util::Subscriber;
+
+use super::GameEvent;
+
+// Prints every game event to stderr, for debugging. Enabled with the --log-events option.
+pub struct EventLog {
+	enabled: bool,
+}
+
+impl EventLog {
+	pub fn new(enabled: bool) -> Self {
+		Self { enabled }
+	}
+}
+
+impl Subscriber<GameEvent> for EventLog {
+	fn notify(&mut self, event: &GameEvent) {
+		if self.enabled {
+			eprintln!("{}", event);
+		}
+	}
+}
@@ -0,0 +1,135 @@
+use std::{error::Error, str::FromStr};
+
+// How boards keep the first reveal from hitting a mine
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum FirstClick {
+	// Mines can be anywhere, the first reveal included, as in Windows 3.1
+	Unprotected,
+	// A mine under the first reveal is moved to the top left corner, or the first free tile after it, as in Windows XP
+	Safe,
+	// The first reveal has no mines around it, so it always opens an area
+	#[default]
+	Opening,
+	// The tiles around the first reveal have no mines around them either, so the 5x5 area around it is clear and it
+	// opens at least a 3x3 area of openings
+	Area,
+}
+
+impl FirstClick {
+	pub const ALL: [FirstClick; 4] = [
+		FirstClick::Unprotected,
+		FirstClick::Safe,
+		FirstClick::Opening,
+		FirstClick::Area,
+	];
+
+	// How far around the first reveal is kept free of mines when mines are placed
+	pub fn clear_radius(self) -> Option<usize> {
+		match self {
+			FirstClick::Unprotected | FirstClick::Safe => None,
+			FirstClick::Opening => Some(1),
+			FirstClick::Area => Some(2),
+		}
+	}
+
+	// Tiles a board needs besides its mines. Even without protection there has to be a safe tile to win by.
+	pub fn reserved_tiles(self) -> usize {
+		match self.clear_radius() {
+			Some(radius) => (radius * 2 + 1).pow(2),
+			None => 1,
+		}
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			FirstClick::Unprotected => "none",
+			FirstClick::Safe => "safe",
+			FirstClick::Opening => "opening",
+			FirstClick::Area => "5x5",
+		}
+	}
+}
+
+impl FromStr for FirstClick {
+	type Err = Box<dyn Error>;
+
+	fn from_str(name: &str) -> Result<Self, Self::Err> {
+		FirstClick::ALL
+			.into_iter()
+			.find(|first_click| first_click.name().eq_ignore_ascii_case(name))
+			.ok_or_else(|| format!("Unknown first click rule '{}', expected none, safe, opening or 5x5", name).into())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{logic::game_board::GameBoard, util::Events};
+
+	// Boards as full as the rule allows, generated for a first reveal at `x`, `y`
+	fn full_boards(first_click: FirstClick, x: usize, y: usize) -> impl Iterator<Item = GameBoard> {
+		(0..50).map(move |seed| {
+			let mut board = GameBoard::new(10, 10, 100 - first_click.reserved_tiles(), first_click).unwrap();
+			board.seed = Some(seed);
+			board.sweep(x, y, &mut Events::default());
+			board
+		})
+	}
+
+	// Whether there are no mines within `radius` tiles of `x`, `y`
+	fn clear_around(board: &GameBoard, x: usize, y: usize, radius: usize) -> bool {
+		let layout = board.layout();
+		(y.saturating_sub(radius)..=y + radius)
+			.flat_map(|y| (x.saturating_sub(radius)..=x + radius).map(move |x| (x, y)))
+			.filter(|&(x, y)| board.is_valid_coord(x, y))
+			.all(|(x, y)| !layout.is_mine(x, y))
+	}
+
+	#[test]
+	fn every_rule_keeps_its_promise() {
+		// Without protection a board this full is almost all mines, wherever the first reveal is
+		assert!(full_boards(FirstClick::Unprotected, 4, 5).any(|board| !clear_around(&board, 4, 5, 0)));
+		for (x, y) in [(4, 5), (0, 0), (9, 3)] {
+			for board in full_boards(FirstClick::Safe, x, y) {
+				assert!(clear_around(&board, x, y, 0));
+				assert_eq!(board.to_text().matches('*').count(), 99);
+			}
+			for first_click in [FirstClick::Opening, FirstClick::Area] {
+				let radius = first_click.clear_radius().unwrap();
+				for board in full_boards(first_click, x, y) {
+					assert!(clear_around(&board, x, y, radius));
+					assert_eq!(board.to_text().matches('*').count(), 100 - first_click.reserved_tiles());
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn boards_need_room_for_the_tiles_the_rule_keeps_clear() {
+		let reserved: Vec<_> = FirstClick::ALL.iter().map(|first_click| first_click.reserved_tiles()).collect();
+		assert_eq!(reserved, [1, 1, 9, 25]);
+		for first_click in FirstClick::ALL {
+			let most = 36 - first_click.reserved_tiles();
+			assert!(GameBoard::new(6, 6, most, first_click).is_ok());
+			assert!(GameBoard::new(6, 6, most + 1, first_click).is_err());
+		}
+		// The smallest board the settings menu allows has no room for mines under the 5x5 rule
+		assert!(GameBoard::new(5, 5, 1, FirstClick::Area).is_err());
+
+		let mut board = GameBoard::new(6, 6, 30, FirstClick::Safe).unwrap();
+		assert!(board.set_first_click(FirstClick::Area).is_err());
+		assert_eq!(board.first_click, FirstClick::Safe);
+		assert!(board.set_first_click(FirstClick::Opening).is_err());
+		assert!(board.update(6, 6, 11).is_ok());
+		assert!(board.set_first_click(FirstClick::Area).is_ok());
+	}
+
+	#[test]
+	fn rules_are_read_by_name() {
+		for first_click in FirstClick::ALL {
+			assert_eq!(first_click.name().parse::<FirstClick>().unwrap(), first_click);
+		}
+		assert_eq!("OPENING".parse::<FirstClick>().unwrap(), FirstClick::Opening);
+		assert!("3x3".parse::<FirstClick>().is_err());
+	}
+}
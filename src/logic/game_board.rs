@@ -1,18 +1,19 @@
-use std::error::Error;
+use std::{error::Error, ops::Range};
 
-use std::collections::VecDeque;
-
-use macroquad::time;
+use std::collections::HashMap;
 
+use super::chunk_store::{ChunkStore, MinePlacer, CHUNK_SIZE};
+use super::first_click::FirstClick;
 use super::tile::{TileModifier, TileState};
+use super::visible::VisibleTile;
 use super::{Events, GameEvent, GameState};
 use crate::logic::tile::Tile;
 
-use crate::util::{ADJACENT_WITHOUT_CENTER, ADJACENT_WITH_CENTER};
+use crate::util::{time_seed, Rng, ADJACENT_WITHOUT_CENTER};
 
 #[derive(Clone, Default)]
 pub struct GameBoard {
-	pub tiles: Vec<Vec<Tile>>,
+	tiles: ChunkStore,
 	width: usize,
 	height: usize,
 
@@ -22,6 +23,106 @@ pub struct GameBoard {
 	flags: usize,
 	mines: usize,
 	pub modify_mode: ModifyMode,
+	pub flag_rules: FlagRules,
+	// Kept across resets and size changes, like `modify_mode`
+	pub first_click: FirstClick,
+	// Boards with a seed get the same mines every time for the same first reveal, else the seed comes from the clock.
+	pub seed: Option<u64>,
+	// Set once the game on the board is over, after which its mines are shown
+	over: bool,
+	// Set when the layout was loaded from text rather than randomly generated.
+	// Resetting such a board keeps its mines so the same puzzle can be retried.
+	preset: bool,
+	// Decides where the mines of randomly generated boards go, one chunk at a time as they are needed
+	generator: Option<MineGenerator>,
+}
+#[derive(Clone)]
+struct MineGenerator {
+	seed: u64,
+	// How many mines each chunk gets, row by row
+	chunk_mines: Vec<usize>,
+	chunks_across: usize,
+	// The first reveal, and how far around it there are never any mines
+	safe_center: (usize, usize),
+	safe_radius: Option<usize>,
+	// Where the mine under the first reveal was moved to, if there was one and the first click rule moves it
+	moved_mine: Option<((usize, usize), (usize, usize))>,
+}
+impl MineGenerator {
+	fn is_safe(&self, x: usize, y: usize) -> bool {
+		let (center_x, center_y) = self.safe_center;
+		self.safe_radius
+			.is_some_and(|radius| x.abs_diff(center_x) <= radius && y.abs_diff(center_y) <= radius)
+	}
+
+	// Where the mines of the chunk with these tiles go. Every position is a mine at most once.
+	fn chunk_layout(&self, xs: Range<usize>, ys: Range<usize>, chunk_x: usize, chunk_y: usize) -> Vec<(usize, usize)> {
+		let mut candidates = vec![];
+		for y in ys.clone() {
+			for x in xs.clone() {
+				if !self.is_safe(x, y) {
+					candidates.push((x, y));
+				}
+			}
+		}
+		let count = self.chunk_mines[chunk_y * self.chunks_across + chunk_x];
+		let mut rng = Rng::derive(self.seed, chunk_x as u64, chunk_y as u64);
+		let mut mines = Vec::with_capacity(count);
+		for i in 0..count {
+			let pick = i + rng.below(candidates.len() - i);
+			candidates.swap(i, pick);
+			mines.push(candidates[i]);
+		}
+		if let Some((from, to)) = self.moved_mine {
+			mines.retain(|&mine| mine != from);
+			if xs.contains(&to.0) && ys.contains(&to.1) {
+				mines.push(to);
+			}
+		}
+		mines
+	}
+}
+// Places the mines a board's generator decides on, within the board's edges. Boards without a generator already
+// have all their mines.
+struct BoardMines<'a> {
+	width: usize,
+	height: usize,
+	generator: Option<&'a MineGenerator>,
+}
+impl MinePlacer for BoardMines<'_> {
+	fn contains(&self, x: i64, y: i64) -> bool {
+		x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+	}
+
+	// Each chunk has its own random generator, so the layout doesn't depend on the order chunks are played in.
+	fn place_mines(&self, chunk_x: i64, chunk_y: i64, tiles: &mut ChunkStore) {
+		let Some(generator) = self.generator else {
+			return;
+		};
+		let (chunk_x, chunk_y) = (chunk_x as usize, chunk_y as usize);
+		let (xs, ys) = chunk_bounds(self.width, self.height, chunk_x, chunk_y);
+		let safe: Vec<_> = xs
+			.clone()
+			.flat_map(|x| ys.clone().map(move |y| (x, y)))
+			.filter(|&(x, y)| generator.is_safe(x, y))
+			.collect();
+		for (x, y) in safe {
+			if let Some(tile) = tiles.get_mut(x as i64, y as i64) {
+				tile.safe = true;
+			}
+		}
+		for (x, y) in generator.chunk_layout(xs, ys, chunk_x, chunk_y) {
+			if let Some(tile) = tiles.get_mut(x as i64, y as i64) {
+				tile.state = TileState::Mine;
+			}
+		}
+	}
+}
+// The tiles of a chunk which are on a board of this size
+fn chunk_bounds(width: usize, height: usize, chunk_x: usize, chunk_y: usize) -> (Range<usize>, Range<usize>) {
+	let x = chunk_x * CHUNK_SIZE;
+	let y = chunk_y * CHUNK_SIZE;
+	(x..(x + CHUNK_SIZE).min(width), y..(y + CHUNK_SIZE).min(height))
 }
 #[derive(Default, Clone)]
 enum BoardState {
@@ -34,44 +135,95 @@ pub enum ModifyMode {
 	#[default]
 	Flag,
 	Question,
+	// Right click puts on or takes off a flag, and the question key does the same with a question mark
+	Separate,
 }
 
-impl GameBoard {
-	pub fn new(width: usize, height: usize, mines: usize) -> Result<Self, Box<dyn Error>> {
-		if width == 0 || height == 0 {
-			return Err("Can't make game board with zero length dimension".into());
+impl ModifyMode {
+	// Right clicks which change one marker to the other, going round the markers as replays are played back: flags
+	// only without question marks, else none, flag and question mark
+	pub fn right_clicks(&self, from: Option<TileModifier>, to: Option<TileModifier>) -> usize {
+		let position = |modifier| match modifier {
+			None => 0,
+			Some(TileModifier::Flagged) => 1,
+			Some(TileModifier::Unsure) => 2,
 		};
-		if (width * height) as isize - 9 < mines as isize {
-			// Can't have more mines than tiles on the game board. Else, the
-			// loop that places mines on board will never complete.
-			return Err("Not enough space for mines".into());
+		match self {
+			ModifyMode::Flag => (from != to) as usize,
+			ModifyMode::Question | ModifyMode::Separate => (position(to) + 3 - position(from)) % 3,
 		}
+	}
+}
 
+// Optional rules for flags, which are kept as boards change like the modify mode
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct FlagRules {
+	// No more flags can be placed than there are mines
+	pub limit: bool,
+	// Winning flags every mine left, so the flag counter ends at zero
+	pub flag_on_win: bool,
+	// Tiles are flagged once a number has only as many unrevealed tiles around it as its count
+	pub flag_obvious: bool,
+}
+
+// Boards need room for their mines besides what the first click rule keeps clear, else they can't all be placed.
+fn check_size(width: usize, height: usize, mines: usize, first_click: FirstClick) -> Result<(), Box<dyn Error>> {
+	if width == 0 || height == 0 {
+		return Err("Can't make game board with zero length dimension".into());
+	};
+	if mines + first_click.reserved_tiles() > width * height {
+		return Err(format!(
+			"Not enough space for mines with the '{}' first click rule",
+			first_click.name()
+		)
+		.into());
+	}
+	Ok(())
+}
+
+impl GameBoard {
+	pub fn new(width: usize, height: usize, mines: usize, first_click: FirstClick) -> Result<Self, Box<dyn Error>> {
+		check_size(width, height, mines, first_click)?;
 		let board = Self {
-			tiles: vec![vec![Tile { ..Default::default() }; height]; width],
 			width,
 			height,
 			mines,
 			non_mine_tiles: width * height - mines,
+			first_click,
 			..Default::default()
 		};
 		Ok(board)
 	}
 
+	// Tiles in parts of the board which haven't been played yet are hidden, and may not know their mine yet.
 	pub fn get_tile(&self, x: usize, y: usize) -> Option<&Tile> {
 		if self.is_valid_coord(x, y) {
-			return Some(&self.tiles[x][y]);
+			return Some(self.tiles.get(x as i64, y as i64));
 		}
 		None
 	}
 
-	pub fn get_tile_mut(&mut self, x: usize, y: usize) -> Option<&mut Tile> {
+	// Generates the tile's part of the board first if needed, so the tile is complete.
+	pub(super) fn get_tile_mut(&mut self, x: usize, y: usize) -> Option<&mut Tile> {
 		if self.is_valid_coord(x, y) {
-			return Some(&mut self.tiles[x][y]);
+			self.prepare_chunk(x / CHUNK_SIZE, y / CHUNK_SIZE);
+			return self.tiles.get_mut(x as i64, y as i64);
 		}
 		None
 	}
 
+	// Whether the board has room for its mines under the rule
+	pub fn has_room(&self, first_click: FirstClick) -> bool {
+		check_size(self.width, self.height, self.mines, first_click).is_ok()
+	}
+
+	// Boards already played on keep the rule they were generated with until they are reset.
+	pub fn set_first_click(&mut self, first_click: FirstClick) -> Result<(), Box<dyn Error>> {
+		check_size(self.width, self.height, self.mines, first_click)?;
+		self.first_click = first_click;
+		Ok(())
+	}
+
 	pub fn is_valid_coord(&self, x: usize, y: usize) -> bool {
 		if x < self.width && y < self.height {
 			return true;
@@ -80,181 +232,776 @@ impl GameBoard {
 	}
 
 	pub fn reset(&mut self) {
-		self.state = BoardState::Ungenerated;
-
+		self.over = false;
 		self.revealed_tiles = 0;
 		self.flags = 0;
-		self.tiles = vec![vec![Tile { ..Default::default() }; self.height]; self.width]
+		if self.preset {
+			for tile in self.tiles.chunks_mut().flat_map(|chunk| chunk.tiles.iter_mut()) {
+				tile.swept = false;
+				tile.modifier = None;
+				tile.highlighted = false;
+			}
+			return;
+		}
+		self.state = BoardState::Ungenerated;
+		self.generator = None;
+		self.tiles.clear();
 	}
-	pub fn update(&mut self, width: usize, height: usize, mines: usize) {
+	pub fn update(&mut self, width: usize, height: usize, mines: usize) -> Result<(), Box<dyn Error>> {
+		check_size(width, height, mines, self.first_click)?;
+		self.over = false;
+		self.preset = false;
 		self.mines = mines;
 		self.height = height;
 		self.width = width;
 		self.non_mine_tiles = width * height - mines;
 		self.state = BoardState::Ungenerated;
+		self.generator = None;
+		self.tiles.clear();
+		Ok(())
 	}
 
-	pub fn remaining_flags(&self) -> isize {
-		self.mines as isize - self.flags as isize
+	pub fn width(&self) -> usize {
+		self.width
 	}
 
-	pub fn modify(&mut self, x: usize, y: usize, event_handler: &mut Events<GameEvent>) {
-		if let Some(&tile) = &self.get_tile(x, y) {
-			if tile.swept {
-				return;
+	pub fn height(&self) -> usize {
+		self.height
+	}
+
+	pub fn mines(&self) -> usize {
+		self.mines
+	}
+
+	// Builds a board with mines at exactly the given positions, skipping random generation.
+	pub fn from_mines(width: usize, height: usize, mines: &[(usize, usize)]) -> Result<Self, Box<dyn Error>> {
+		if width == 0 || height == 0 {
+			return Err("Can't make game board with zero length dimension".into());
+		};
+
+		let mut tiles = ChunkStore::default();
+		for chunk_y in 0..height.div_ceil(CHUNK_SIZE) {
+			for chunk_x in 0..width.div_ceil(CHUNK_SIZE) {
+				tiles.chunk_mut(chunk_x as i64, chunk_y as i64).mined = true;
+			}
+		}
+		let mut mine_count = 0;
+		for &(x, y) in mines {
+			if x >= width || y >= height {
+				return Err(format!("Mine at ({}, {}) is outside of a {}x{} board", x, y, width, height).into());
 			}
-			let modifier = if let Some(modifier) = tile.modifier {
-				match modifier {
-					TileModifier::Flagged => {
-						self.flags -= 1;
-						match self.modify_mode {
-							ModifyMode::Flag => {
-								event_handler.add(GameEvent::FlagTile(x, y, tile));
-								None
-							}
-							ModifyMode::Question => {
-								event_handler.add(GameEvent::QuestionTile(x, y, tile));
-
-								Some(TileModifier::Unsure)
-							}
+			if let Some(tile) = tiles.get_mut(x as i64, y as i64) {
+				if tile.state != TileState::Mine {
+					tile.state = TileState::Mine;
+					mine_count += 1;
+				}
+			}
+		}
+		if mine_count == width * height {
+			return Err("Board must have at least one safe tile".into());
+		}
+
+		let mut board = Self {
+			tiles,
+			width,
+			height,
+			mines: mine_count,
+			non_mine_tiles: width * height - mine_count,
+			state: BoardState::Generated,
+			preset: true,
+			..Default::default()
+		};
+		board.generate_all();
+		Ok(board)
+	}
+
+	// Text layout format:
+	//
+	//     3x2
+	//     *..
+	//     .*.
+	//
+	// The header gives the dimensions as WIDTHxHEIGHT, followed by one line per row where
+	// `*` is a mine and `.` is a safe tile. Blank lines and lines starting with `#` are ignored.
+	pub fn from_text(text: &str) -> Result<Self, Box<dyn Error>> {
+		let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+		let header = lines.next().ok_or("Board text is empty")?;
+		let (width, height) = header.split_once('x').ok_or("Board header must be in the form WIDTHxHEIGHT")?;
+		let width: usize = width.trim().parse()?;
+		let height: usize = height.trim().parse()?;
+
+		let mut mines = vec![];
+		for y in 0..height {
+			let row = lines.next().ok_or(format!("Board is missing row {}", y + 1))?;
+			if row.chars().count() != width {
+				return Err(format!("Row {} has {} tiles, expected {}", y + 1, row.chars().count(), width).into());
+			}
+			for (x, symbol) in row.chars().enumerate() {
+				match symbol {
+					'*' => mines.push((x, y)),
+					'.' => (),
+					_ => return Err(format!("Unknown tile '{}' at row {}, column {}", symbol, y + 1, x + 1).into()),
+				}
+			}
+		}
+		if lines.next().is_some() {
+			return Err(format!("Board has more than {} rows", height).into());
+		}
+		Self::from_mines(width, height, &mines)
+	}
+
+	// Copy of the mine layout with all play progress cleared, such that it can be replayed.
+	// Every part of the board is generated in the copy, so all of its mines are known. Only the crate's own tools
+	// see it, everyone else gets the layout through `shown_text` once the game is over.
+	pub(crate) fn layout(&self) -> Self {
+		let mut board = self.clone();
+		board.generate_all();
+		board.generator = None;
+		board.preset = true;
+		board.reset();
+		board
+	}
+
+	// Only knows about mines in parts of the board which have been generated, see `layout`.
+	pub(crate) fn is_mine(&self, x: usize, y: usize) -> bool {
+		matches!(self.get_tile(x, y), Some(tile) if tile.state == TileState::Mine)
+	}
+
+	// The board's 3BV: the fewest clicks which clear it, being one per opening and one per number not on the edge
+	// of an opening. Only counts parts of the board which have been generated, see `layout`.
+	pub(crate) fn bbbv(&self) -> usize {
+		let (openings, cleared) = self.flood_openings();
+		let numbers = (0..self.height)
+			.flat_map(|y| (0..self.width).map(move |x| (x, y)))
+			.filter(|&(x, y)| !cleared[y * self.width + x] && !self.is_mine(x, y))
+			.count();
+		openings.len() + numbers
+	}
+
+	// One tile of each opening, an area of tiles without adjacent mines which a single click clears along with its
+	// edge. Only counts parts of the board which have been generated, see `layout`.
+	pub(crate) fn openings(&self) -> Vec<(usize, usize)> {
+		self.flood_openings().0
+	}
+
+	// The first tile found of each opening, and which tiles the openings clear
+	fn flood_openings(&self) -> (Vec<(usize, usize)>, Vec<bool>) {
+		let index = |x: usize, y: usize| y * self.width + x;
+		let is_opening = |x: usize, y: usize| !self.is_mine(x, y) && self.get_tile(x, y).is_some_and(|tile| tile.adjacent == 0);
+		let mut cleared = vec![false; self.width * self.height];
+		let mut starts = vec![];
+		for y in 0..self.height {
+			for x in 0..self.width {
+				if cleared[index(x, y)] || !is_opening(x, y) {
+					continue;
+				}
+				starts.push((x, y));
+				cleared[index(x, y)] = true;
+				let mut openings = vec![(x, y)];
+				while let Some((x, y)) = openings.pop() {
+					for &(offset_x, offset_y) in ADJACENT_WITHOUT_CENTER.iter() {
+						let (Some(x), Some(y)) = (x.checked_add_signed(offset_x), y.checked_add_signed(offset_y)) else {
+							continue;
+						};
+						if !self.is_valid_coord(x, y) || cleared[index(x, y)] {
+							continue;
+						}
+						cleared[index(x, y)] = true;
+						if is_opening(x, y) {
+							openings.push((x, y));
 						}
 					}
-					TileModifier::Unsure => None,
 				}
-			} else {
-				self.flags += 1;
-				event_handler.add(GameEvent::FlagTile(x, y, tile));
-				Some(TileModifier::Flagged)
-			};
-			if let Some(tile) = self.get_tile_mut(x, y) {
-				tile.modifier = modifier;
 			}
 		}
+		(starts, cleared)
+	}
+
+	// Hidden, unflagged tiles around a revealed number whose flag count matches it.
+	// These are the tiles a chord would reveal.
+	pub fn chord_targets(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+		if !self.is_valid_coord(x, y) {
+			return vec![];
+		}
+		let placer = BoardMines {
+			width: self.width,
+			height: self.height,
+			generator: self.generator.as_ref(),
+		};
+		let targets = self.tiles.chord_targets(x as i64, y as i64, &placer);
+		targets.into_iter().map(|(x, y)| (x as usize, y as usize)).collect()
+	}
+
+	// Unflagged hidden tiles around a revealed number with only as many unrevealed tiles around it as its count,
+	// which must all be mines
+	pub fn obvious_mines(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+		let Some(tile) = self.get_tile(x, y) else {
+			return vec![];
+		};
+		if !tile.swept || tile.state == TileState::Mine || tile.adjacent == 0 {
+			return vec![];
+		}
+		let mut unrevealed = 0;
+		let mut targets = vec![];
+		for &(offset_x, offset_y) in ADJACENT_WITHOUT_CENTER.iter() {
+			let x = x as isize + offset_x;
+			let y = y as isize + offset_y;
+			if x < 0 || y < 0 {
+				continue;
+			}
+			let x = x as usize;
+			let y = y as usize;
+			if let Some(tile) = self.get_tile(x, y) {
+				// Mines set off in a hot-seat game are as good as flagged
+				if !tile.swept || tile.state == TileState::Mine {
+					unrevealed += 1;
+				}
+				if !tile.swept && tile.modifier.is_none() {
+					targets.push((x, y));
+				}
+			}
+		}
+		if unrevealed != tile.adjacent {
+			return vec![];
+		}
+		targets
+	}
+
+	// Writes the mine layout in the format read by `from_text`.
+	// A board which has not been generated yet has no mines to write.
+	pub(crate) fn to_text(&self) -> String {
+		let layout = self.layout();
+		let mut text = format!("{}x{}\n", self.width, self.height);
+		for y in 0..self.height {
+			for x in 0..self.width {
+				text.push(if layout.is_mine(x, y) { '*' } else { '.' });
+			}
+			text.push('\n');
+		}
+		text
+	}
+
+	// Copies a tile from the board this one mirrors, such as the server's in a shared game, returning it as it now
+	// is. Mirrors never generate mines of their own, they only know what they are told.
+	pub fn mirror_tile(&mut self, x: usize, y: usize, visible: VisibleTile) -> Option<Tile> {
+		let old = *self.get_tile_mut(x, y)?;
+		let tile = match visible {
+			VisibleTile::Hidden => Tile { modifier: None, ..old },
+			VisibleTile::Flagged => Tile {
+				modifier: Some(TileModifier::Flagged),
+				..old
+			},
+			VisibleTile::Questioned => Tile {
+				modifier: Some(TileModifier::Unsure),
+				..old
+			},
+			VisibleTile::Revealed(adjacent) => Tile {
+				state: TileState::Empty,
+				adjacent,
+				swept: true,
+				modifier: None,
+				..old
+			},
+			VisibleTile::Exploded => Tile {
+				state: TileState::Mine,
+				swept: true,
+				modifier: None,
+				..old
+			},
+		};
+		self.revealed_tiles = self.revealed_tiles + tile.swept as usize - old.swept as usize;
+		let is_flagged = |tile: Tile| tile.modifier == Some(TileModifier::Flagged);
+		self.flags = self.flags + is_flagged(tile) as usize - is_flagged(old) as usize;
+		*self.get_tile_mut(x, y)? = tile;
+		Some(tile)
+	}
+
+	// Marks where a mine was in the board this one mirrors, once its game is over.
+	pub fn mirror_mine(&mut self, x: usize, y: usize) {
+		if let Some(tile) = self.get_tile_mut(x, y) {
+			tile.state = TileState::Mine;
+		}
+	}
+
+	// Shows the mines of a mirrored board, whose game was decided elsewhere
+	pub(super) fn end(&mut self) {
+		self.over = true;
+	}
+
+	// Whether a tile holds a mine, once the game is over and the mines are shown. None while it is being played.
+	pub fn shown_mine(&self, x: usize, y: usize) -> Option<bool> {
+		self.over.then(|| self.is_mine(x, y))
+	}
+
+	// The mine layout as `to_text` writes it, once the game is over. None while it is being played.
+	pub fn shown_text(&self) -> Option<String> {
+		self.over.then(|| self.to_text())
+	}
+
+	pub fn remaining_flags(&self) -> isize {
+		self.mines as isize - self.flags as isize
+	}
+
+	// What a right click does, which goes round the markers the modify mode has
+	pub fn modify(&mut self, x: usize, y: usize, event_handler: &mut Events<GameEvent>) {
+		let Some(&tile) = self.get_tile(x, y) else {
+			return;
+		};
+		let modifier = match (tile.modifier, &self.modify_mode) {
+			(None, _) => Some(TileModifier::Flagged),
+			(Some(TileModifier::Flagged), ModifyMode::Question) => Some(TileModifier::Unsure),
+			(Some(TileModifier::Flagged), _) => None,
+			(Some(TileModifier::Unsure), ModifyMode::Separate) => Some(TileModifier::Flagged),
+			(Some(TileModifier::Unsure), _) => None,
+		};
+		self.set_modifier(x, y, tile, modifier, event_handler);
+	}
+
+	// What the question key does, in the mode with a key of its own for question marks
+	pub fn question(&mut self, x: usize, y: usize, event_handler: &mut Events<GameEvent>) {
+		let Some(&tile) = self.get_tile(x, y) else {
+			return;
+		};
+		if !matches!(self.modify_mode, ModifyMode::Separate) {
+			return;
+		}
+		let modifier = match tile.modifier {
+			Some(TileModifier::Unsure) => None,
+			_ => Some(TileModifier::Unsure),
+		};
+		self.set_modifier(x, y, tile, modifier, event_handler);
+	}
+
+	// Flags a tile whatever the modify mode, for flags the game places rather than the player
+	pub(super) fn flag(&mut self, x: usize, y: usize, event_handler: &mut Events<GameEvent>) {
+		if let Some(&tile) = self.get_tile(x, y) {
+			self.set_modifier(x, y, tile, Some(TileModifier::Flagged), event_handler);
+		}
+	}
+
+	fn set_modifier(
+		&mut self,
+		x: usize,
+		y: usize,
+		tile: Tile,
+		modifier: Option<TileModifier>,
+		event_handler: &mut Events<GameEvent>,
+	) {
+		let flagged = modifier == Some(TileModifier::Flagged);
+		let was_flagged = tile.modifier == Some(TileModifier::Flagged);
+		if tile.swept || (flagged && !was_flagged && self.flag_rules.limit && self.flags >= self.mines) {
+			return;
+		}
+		// Any change to a flag is a flag event, even when a question mark takes its place
+		if flagged || was_flagged {
+			event_handler.add(GameEvent::FlagTile(x, y, tile));
+		} else {
+			event_handler.add(GameEvent::QuestionTile(x, y, tile));
+		}
+		self.flags = self.flags + flagged as usize - was_flagged as usize;
+		if let Some(tile) = self.get_tile_mut(x, y) {
+			tile.modifier = modifier;
+		}
+	}
+
+	// Whether the tile is a mine which hasn't been flagged or set off. Generates the tile's part of the board if needed.
+	pub(super) fn is_hidden_mine(&mut self, x: usize, y: usize) -> bool {
+		self.get_tile_mut(x, y)
+			.is_some_and(|tile| tile.state == TileState::Mine && tile.modifier != Some(TileModifier::Flagged) && !tile.swept)
+	}
+
+	// Sets off a mine without ending the game, for hot-seat games where only the player who hit it pays.
+	pub(super) fn detonate(&mut self, x: usize, y: usize, event_handler: &mut Events<GameEvent>) {
+		if let Some(tile) = self.get_tile_mut(x, y) {
+			tile.swept = true;
+			event_handler.add(GameEvent::Detonate(x, y, *tile));
+		}
 	}
 
 	pub fn sweep(&mut self, x: usize, y: usize, event_handler: &mut Events<GameEvent>) -> Option<GameState> {
 		if let BoardState::Ungenerated = self.state {
 			self.generate(x, y);
 		}
-		let tile = self.tiles[x][y];
-		if tile.modifier.is_some() {
+		let tile = *self.get_tile_mut(x, y)?;
+		// Question marks don't stop a tile being revealed, only flags do
+		if tile.modifier == Some(TileModifier::Flagged) {
 			return None;
 		}
 		if tile.swept {
 			return None;
 		}
-		self.tiles[x][y].swept = true;
+		let revealed_tile = self.get_tile_mut(x, y)?;
+		revealed_tile.swept = true;
+		revealed_tile.modifier = None;
+		let revealed_tile = *revealed_tile;
 		self.revealed_tiles += 1;
-		event_handler.add(GameEvent::RevealTile(x, y, self.tiles[x][y]));
+		event_handler.add(GameEvent::RevealTile(x, y, revealed_tile));
 
 		if tile.state == TileState::Mine {
 			event_handler.add(GameEvent::Lose(x, y, tile));
-
-			event_handler.add(GameEvent::GameEnd(self.clone()));
+			// Every mine is shown once the game is over
+			self.generate_all();
+			// Set first, so whoever hears of the end can see the mines
+			self.over = true;
+			event_handler.add(GameEvent::GameEnd(Box::new(self.clone())));
 			return Some(GameState::GameOver);
 		};
 		event_handler.add(GameEvent::SweepBegin);
 
-		let mut scan_list = VecDeque::from([(x, y)]);
-		let mut revealed: usize = 0;
-		while !scan_list.is_empty() {
-			for &scan_location in ADJACENT_WITHOUT_CENTER.iter() {
-				if let Some((x, y)) = scan_list.front() {
-					if let Some(old_tile) = self.get_tile(*x, *y) {
-						if old_tile.adjacent > 0 {
-							continue;
-						}
-						let x = *x as isize + scan_location.0;
-						let y = *y as isize + scan_location.1;
-
-						if x < 0 || y < 0 {
-							continue;
-						}
-						let y = y as usize;
-						let x = x as usize;
-						if let Some(tile) = self.get_tile_mut(x, y) {
-							if tile.swept {
-								continue;
-							}
-							scan_list.push_back((x, y));
-							tile.swept = true;
-							revealed += 1;
-							event_handler.add(GameEvent::RevealTile(x, y, *tile));
-						}
-					}
-				}
-			}
-			scan_list.pop_front();
+		let placer = BoardMines {
+			width: self.width,
+			height: self.height,
+			generator: self.generator.as_ref(),
+		};
+		let (revealed, flags) = self.tiles.flood(x as i64, y as i64, &placer);
+		self.flags -= flags;
+		for &(x, y) in &revealed {
+			event_handler.add(GameEvent::RevealTile(x as usize, y as usize, *self.tiles.get(x, y)));
 		}
-		self.revealed_tiles += revealed;
+		self.revealed_tiles += revealed.len();
 		if self.revealed_tiles == self.non_mine_tiles {
+			if self.flag_rules.flag_on_win {
+				self.flag_mines();
+			}
 			event_handler.add(GameEvent::Win);
-			event_handler.add(GameEvent::GameEnd(self.clone()));
+			self.over = true;
+			event_handler.add(GameEvent::GameEnd(Box::new(self.clone())));
 			return Some(GameState::Victory);
 		}
 		None
 	}
 
+	// Flags every mine which hasn't been set off, and clears markers from the rest, which have all been revealed
+	fn flag_mines(&mut self) {
+		self.generate_all();
+		self.flags = 0;
+		for tile in self.tiles.chunks_mut().flat_map(|chunk| chunk.tiles.iter_mut()) {
+			if tile.state == TileState::Mine && !tile.swept {
+				tile.modifier = Some(TileModifier::Flagged);
+				self.flags += 1;
+			} else {
+				tile.modifier = None;
+			}
+		}
+	}
+
+	// Decides how many mines each chunk gets, keeping the first reveal safe as the first click rule says.
+	// Chunks get their mines when first needed, see `place_mines`.
 	fn generate(&mut self, avoid_x: usize, avoid_y: usize) {
-		let width = self.width;
-		let height = self.height;
-		// Make list of all safe positions which are actually on board,
-		//removing all which are before, or past the bounds of the board.
-		let mut valid_safezone: Vec<(isize, isize)> = ADJACENT_WITH_CENTER.to_vec();
-		valid_safezone.retain_mut(|pos: &mut (isize, isize)| {
-			let adjusted_x = pos.0 + avoid_x as isize;
-			let adjusted_y = pos.1 + avoid_y as isize;
-			if adjusted_x >= 0 && adjusted_y >= 0 && adjusted_x < width as isize && adjusted_y < height as isize {
-				pos.0 = adjusted_x;
-				pos.1 = adjusted_y;
-				return true;
+		let seed = self.seed.unwrap_or_else(time_seed);
+		let mut rng = Rng::new(seed);
+		let mut generator = MineGenerator {
+			seed,
+			chunk_mines: vec![],
+			chunks_across: self.width.div_ceil(CHUNK_SIZE),
+			safe_center: (avoid_x, avoid_y),
+			safe_radius: self.first_click.clear_radius(),
+			moved_mine: None,
+		};
+		// Tiles each chunk has room for mines on, row by row
+		let mut capacities = vec![];
+		for chunk_y in 0..self.height.div_ceil(CHUNK_SIZE) {
+			for chunk_x in 0..generator.chunks_across {
+				let (xs, ys) = chunk_bounds(self.width, self.height, chunk_x, chunk_y);
+				let safe_in_chunk = xs
+					.clone()
+					.flat_map(|x| ys.clone().map(move |y| (x, y)))
+					.filter(|&(x, y)| generator.is_safe(x, y))
+					.count();
+				capacities.push(xs.len() * ys.len() - safe_in_chunk);
+			}
+		}
+		let mut remaining_tiles: usize = capacities.iter().sum();
+		let mut remaining_mines = self.mines;
+		for capacity in capacities {
+			// Each tile gets a mine with the chance of the mines left over the tiles left, which spreads the
+			// mines exactly as if they were placed one at a time across the whole board.
+			let mut mines = 0;
+			for _ in 0..capacity {
+				if rng.below(remaining_tiles) < remaining_mines {
+					mines += 1;
+					remaining_mines -= 1;
+				}
+				remaining_tiles -= 1;
+			}
+			generator.chunk_mines.push(mines);
+		}
+
+		if self.first_click == FirstClick::Safe {
+			// Finding the first free tile only lays out the chunks it has to look through
+			let mut layouts = HashMap::new();
+			let mut is_mine = |x: usize, y: usize| {
+				let chunk = (x / CHUNK_SIZE, y / CHUNK_SIZE);
+				let layout = layouts.entry(chunk).or_insert_with(|| {
+					let (xs, ys) = chunk_bounds(self.width, self.height, chunk.0, chunk.1);
+					generator.chunk_layout(xs, ys, chunk.0, chunk.1)
+				});
+				layout.contains(&(x, y))
+			};
+			if is_mine(avoid_x, avoid_y) {
+				let free = (0..self.height)
+					.flat_map(|y| (0..self.width).map(move |x| (x, y)))
+					.find(|&(x, y)| (x, y) != (avoid_x, avoid_y) && !is_mine(x, y));
+				generator.moved_mine = free.map(|free| ((avoid_x, avoid_y), free));
+			}
+		}
+
+		self.generator = Some(generator);
+		self.state = BoardState::Generated;
+	}
+
+	// Generates every chunk of the board, for when all mines need to be known.
+	fn generate_all(&mut self) {
+		for chunk_y in 0..self.height.div_ceil(CHUNK_SIZE) {
+			for chunk_x in 0..self.width.div_ceil(CHUNK_SIZE) {
+				self.prepare_chunk(chunk_x, chunk_y);
 			}
-			false
-		});
-		for safe_pos in valid_safezone.iter() {
-			let safe_x = safe_pos.0 as usize;
-			let safe_y = safe_pos.1 as usize;
-			self.tiles[safe_x][safe_y].safe = true;
 		}
+	}
 
-		let mut i = 0;
+	// Makes sure a chunk exists, and once the board is generated, that its tiles know their mines and adjacent counts.
+	fn prepare_chunk(&mut self, chunk_x: usize, chunk_y: usize) {
+		if let BoardState::Ungenerated = self.state {
+			self.tiles.chunk_mut(chunk_x as i64, chunk_y as i64);
+			return;
+		}
+		let placer = BoardMines {
+			width: self.width,
+			height: self.height,
+			generator: self.generator.as_ref(),
+		};
+		self.tiles.prepare_chunk(chunk_x as i64, chunk_y as i64, &placer);
+	}
+}
 
-		let seed = (time::get_time() * 1000000.0) as u64;
-		macroquad::rand::srand(seed);
-		while i != self.mines {
-			let x = macroquad::rand::gen_range(0, width);
-			let y = macroquad::rand::gen_range(0, height);
-			let mut tile = &mut self.tiles[x][y];
+#[cfg(test)]
+mod tests {
+	use super::*;
 
-			if tile.state == TileState::Mine || tile.safe {
-				continue;
+	const BOARD: &str = "4x3\n*..*\n....\n.**.\n";
+
+	#[test]
+	fn text_round_trips() {
+		let board = GameBoard::from_text(BOARD).unwrap();
+		assert_eq!((board.width(), board.height(), board.mines()), (4, 3, 4));
+		assert_eq!(board.to_text(), BOARD);
+	}
+
+	// The board the game's end was announced with, if it ended
+	fn ended_board(events: &mut Events<GameEvent>) -> Option<GameBoard> {
+		std::iter::from_fn(|| events.pop()).find_map(|event| match event {
+			GameEvent::GameEnd(board) => Some(*board),
+			_ => None,
+		})
+	}
+
+	#[test]
+	fn ended_games_show_their_mines() {
+		let mut events = Events::default();
+		let mut lost = GameBoard::from_text(BOARD).unwrap();
+		assert!(lost.sweep(0, 0, &mut events) == Some(GameState::GameOver));
+		let ended = ended_board(&mut events).unwrap();
+		assert_eq!(ended.shown_text().as_deref(), Some(BOARD));
+		assert_eq!(ended.shown_mine(3, 0), Some(true));
+		assert_eq!(ended.shown_mine(1, 0), Some(false));
+
+		let mut won = GameBoard::from_text("3x1\n*..\n").unwrap();
+		assert_eq!(won.shown_mine(0, 0), None);
+		assert!(won.sweep(2, 0, &mut events) == Some(GameState::Victory));
+		assert_eq!(ended_board(&mut events).unwrap().shown_text().as_deref(), Some("3x1\n*..\n"));
+	}
+
+	#[test]
+	fn the_flag_limit_stops_at_the_mine_count() {
+		let mut events = Events::default();
+		let mut board = GameBoard::from_text(BOARD).unwrap();
+		board.flag_rules.limit = true;
+		for x in 0..4 {
+			board.modify(x, 1, &mut events);
+		}
+		assert_eq!(board.flags, 4);
+		board.modify(0, 0, &mut events);
+		assert_eq!(board.flags, 4);
+		assert!(board.get_tile(0, 0).unwrap().modifier.is_none());
+
+		// Taking a flag away makes room for another
+		board.modify(0, 1, &mut events);
+		board.modify(0, 0, &mut events);
+		assert_eq!(board.flags, 4);
+		assert!(board.get_tile(0, 0).unwrap().modifier == Some(TileModifier::Flagged));
+	}
+
+	#[test]
+	fn winning_flags_the_mines_left() {
+		let mut board = GameBoard::from_text("3x2\n*..\n..*\n").unwrap();
+		board.flag_rules.flag_on_win = true;
+		let mut events = Events::default();
+		board.modify(2, 1, &mut events);
+		for (x, y) in [(1, 0), (2, 0), (0, 1), (1, 1)] {
+			board.sweep(x, y, &mut events);
+		}
+		assert_eq!(board.flags, 2);
+		assert!(board.get_tile(0, 0).unwrap().modifier == Some(TileModifier::Flagged));
+		assert!(board.get_tile(2, 1).unwrap().modifier == Some(TileModifier::Flagged));
+	}
+
+	#[test]
+	fn obvious_mines_need_a_number_with_no_other_way_out() {
+		let mut events = Events::default();
+		let mut board = GameBoard::from_text("3x3\n*..\n...\n...\n").unwrap();
+		assert!(board.obvious_mines(1, 1).is_empty());
+		board.sweep(2, 2, &mut events);
+		assert_eq!(board.obvious_mines(1, 1), vec![(0, 0)]);
+		assert_eq!(board.obvious_mines(0, 1), vec![(0, 0)]);
+		// Blank tiles and flagged mines have nothing left to flag
+		assert!(board.obvious_mines(2, 2).is_empty());
+		board.modify(0, 0, &mut events);
+		assert!(board.obvious_mines(1, 1).is_empty());
+
+		// The middle of this one still has more hidden tiles around it than mines
+		let mut board = GameBoard::from_text(BOARD).unwrap();
+		board.sweep(1, 1, &mut events);
+		assert!(board.obvious_mines(1, 1).is_empty());
+	}
+
+	// The marker on the top left tile after each of `clicks`, where true is a right click and false the question key
+	fn markers(mode: ModifyMode, clicks: &[bool]) -> Vec<Option<TileModifier>> {
+		let mut board = GameBoard::from_text(BOARD).unwrap();
+		board.modify_mode = mode;
+		let mut events = Events::default();
+		let mut markers = vec![];
+		for &right_click in clicks {
+			if right_click {
+				board.modify(0, 0, &mut events);
+			} else {
+				board.question(0, 0, &mut events);
 			}
+			markers.push(board.get_tile(0, 0).unwrap().modifier);
+		}
+		markers
+	}
 
-			tile.state = TileState::Mine;
+	#[test]
+	fn markers_cycle_as_the_modify_mode_says() {
+		use TileModifier::*;
+		assert!(markers(ModifyMode::Flag, &[true, true, false]) == [Some(Flagged), None, None]);
+		assert!(markers(ModifyMode::Question, &[true, true, true, false]) == [Some(Flagged), Some(Unsure), None, None]);
+		assert!(
+			markers(ModifyMode::Separate, &[true, true, false, false, false, true, true])
+				== [Some(Flagged), None, Some(Unsure), None, Some(Unsure), Some(Flagged), None]
+		);
+	}
 
-			i += 1;
-		}
-
-		for x in 0..width {
-			for y in 0..height {
-				if let Some(tile) = self.get_tile(x, y) {
-					if tile.state == TileState::Mine {
-						for &scan_location in ADJACENT_WITH_CENTER.iter() {
-							let new_x = x as isize + scan_location.0;
-							let new_y = y as isize + scan_location.1;
-							if new_x < 0 || new_y < 0 {
-								continue;
-							}
-							let new_x = new_x as usize;
-							let new_y = new_y as usize;
-							if let Some(tile) = self.get_tile_mut(new_x, new_y) {
-								tile.increment_adjacent();
-							}
-						}
-					}
+	#[test]
+	fn right_clicks_go_round_the_markers_replays_play_back() {
+		use TileModifier::*;
+		let markers = [None, Some(Flagged), Some(Unsure)];
+		// Clicks from each marker to each other one, in the order above
+		let table = [
+			(ModifyMode::Flag, [[0, 1, 1], [1, 0, 1], [1, 1, 0]]),
+			(ModifyMode::Question, [[0, 1, 2], [2, 0, 1], [1, 2, 0]]),
+			(ModifyMode::Separate, [[0, 1, 2], [2, 0, 1], [1, 2, 0]]),
+		];
+		for (mode, clicks) in table {
+			for (from, row) in markers.iter().zip(clicks) {
+				for (to, clicks) in markers.iter().zip(row) {
+					assert_eq!(mode.right_clicks(*from, *to), clicks, "{:?}", mode);
 				}
 			}
 		}
-		self.state = BoardState::Generated;
+
+		// Played back with question marks, as replays of either mode with them are, the clicks land on the marker
+		let mut board = GameBoard::from_text(BOARD).unwrap();
+		board.modify_mode = ModifyMode::Question;
+		let mut events = Events::default();
+		for &from in &markers {
+			for &to in &markers {
+				while board.get_tile(0, 0).unwrap().modifier != from {
+					board.modify(0, 0, &mut events);
+				}
+				for _ in 0..ModifyMode::Separate.right_clicks(from, to) {
+					board.modify(0, 0, &mut events);
+				}
+				assert!(board.get_tile(0, 0).unwrap().modifier == to);
+			}
+		}
+	}
+
+	#[test]
+	fn question_marks_dont_stop_a_reveal() {
+		let mut board = GameBoard::from_text(BOARD).unwrap();
+		board.modify_mode = ModifyMode::Separate;
+		let mut events = Events::default();
+		board.question(1, 0, &mut events);
+		board.modify(2, 0, &mut events);
+		assert!(board.sweep(1, 0, &mut events).is_none());
+		assert!(board.sweep(2, 0, &mut events).is_none());
+		let questioned = *board.get_tile(1, 0).unwrap();
+		assert!(questioned.swept && questioned.modifier.is_none());
+		assert!(!board.get_tile(2, 0).unwrap().swept);
+		assert_eq!(board.flags, 1);
+	}
+
+	#[test]
+	fn text_skips_comments_and_blank_lines() {
+		let text = "# Seed 7\n\n4x3\n*..*\n  ....  \n\n.**.\n# The end\n";
+		assert_eq!(GameBoard::from_text(text).unwrap().to_text(), BOARD);
+	}
+
+	#[test]
+	fn generated_boards_round_trip() {
+		let mut board = GameBoard::new(40, 35, 300, FirstClick::default()).unwrap();
+		board.seed = Some(11);
+		board.sweep(20, 17, &mut Events::default());
+		let text = board.to_text();
+		assert_eq!(text.matches('*').count(), 300);
+		assert_eq!(GameBoard::from_text(&text).unwrap().to_text(), text);
+	}
+
+	// A board of several chunks, with its mines generated for a first reveal in the middle
+	fn chunked_board(seed: u64) -> GameBoard {
+		let mut board = GameBoard::new(100, 80, 1500, FirstClick::default()).unwrap();
+		board.seed = Some(seed);
+		board.sweep(50, 40, &mut Events::default());
+		board
+	}
+
+	#[test]
+	fn seeded_boards_are_the_same_whichever_order_chunks_are_laid_out_in() {
+		let layout = chunked_board(3).to_text();
+		assert_eq!(layout.matches('*').count(), 1500);
+		assert_eq!(chunked_board(3).to_text(), layout);
+
+		let mut backwards = chunked_board(3);
+		for chunk_y in (0..80usize.div_ceil(CHUNK_SIZE)).rev() {
+			for chunk_x in (0..100usize.div_ceil(CHUNK_SIZE)).rev() {
+				backwards.prepare_chunk(chunk_x, chunk_y);
+			}
+		}
+		assert_eq!(backwards.to_text(), layout);
+
+		assert_ne!(chunked_board(4).to_text(), layout);
+	}
+
+	#[test]
+	fn bad_text_is_refused() {
+		let bad = [
+			("", "Board text is empty"),
+			("4 by 3\n", "Board header must be in the form WIDTHxHEIGHT"),
+			("4x3\n*..*\n....\n", "Board is missing row 3"),
+			("4x3\n*..*\n.....\n.**.\n", "Row 2 has 5 tiles, expected 4"),
+			("4x3\n*..*\n..o.\n.**.\n", "Unknown tile 'o' at row 2, column 3"),
+			("4x3\n*..*\n....\n.**.\n....\n", "Board has more than 3 rows"),
+			("2x1\n**\n", "Board must have at least one safe tile"),
+		];
+		for (text, error) in bad {
+			assert_eq!(
+				GameBoard::from_text(text).err().map(|error| error.to_string()).as_deref(),
+				Some(error)
+			);
+		}
+		assert!(GameBoard::from_text("ax3\n").is_err());
+		assert!(GameBoard::from_text("0x0\n").is_err());
 	}
 }
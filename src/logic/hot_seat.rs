@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use super::{
+	tile::{TileModifier, TileState},
+	GameEvent,
+};
+use crate::util::Subscriber;
+
+pub const MAX_PLAYERS: usize = 4;
+// Points for each tile a player clears
+const CLEAR_POINTS: isize = 1;
+// Points won for each of a player's flags on a mine, and lost for each one on a safe tile, once the game is over
+const FLAG_POINTS: isize = 5;
+// Points lost for hitting a mine under `MineRule::Penalty`
+const MINE_PENALTY: isize = 25;
+
+// What happens to a player who hits a mine
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum MineRule {
+	#[default]
+	Penalty,
+	Eliminate,
+}
+
+#[derive(Clone, Default)]
+pub struct Player {
+	pub score: isize,
+	pub eliminated: bool,
+}
+
+// Players taking turns on one board. A turn is one reveal or chord; flags can be placed freely during a
+// turn and belong to whoever placed them, so only they can take them away again. Flags are scored when
+// the game ends, so placing one doesn't tell anyone whether it was right.
+#[derive(Clone)]
+pub struct HotSeat {
+	pub players: Vec<Player>,
+	pub current: usize,
+	pub mine_rule: MineRule,
+	flag_owners: HashMap<(usize, usize), usize>,
+	// Set when the current action revealed something, so the turn passes once it is done
+	turn_taken: bool,
+}
+
+impl HotSeat {
+	pub fn new(players: usize, mine_rule: MineRule) -> Self {
+		Self {
+			players: vec![Player::default(); players.clamp(2, MAX_PLAYERS)],
+			current: 0,
+			mine_rule,
+			flag_owners: HashMap::new(),
+			turn_taken: false,
+		}
+	}
+
+	pub fn reset(&mut self) {
+		*self = Self::new(self.players.len(), self.mine_rule);
+	}
+
+	// Whether the current player may change the flag on a tile
+	pub fn may_modify(&self, x: usize, y: usize) -> bool {
+		self.flag_owners.get(&(x, y)).is_none_or(|&owner| owner == self.current)
+	}
+
+	pub fn players_left(&self) -> usize {
+		self.players.iter().filter(|player| !player.eliminated).count()
+	}
+
+	// Whether the game goes on if the current player hits a mine
+	pub fn survives_mine(&self) -> bool {
+		self.mine_rule == MineRule::Penalty || self.players_left() > 1
+	}
+
+	// Passes the turn to the next player still in the game, if the last action took a turn.
+	pub fn end_turn(&mut self) {
+		if !self.turn_taken || self.players_left() == 0 {
+			return;
+		}
+		self.turn_taken = false;
+		loop {
+			self.current = (self.current + 1) % self.players.len();
+			if !self.players[self.current].eliminated {
+				break;
+			}
+		}
+	}
+}
+
+impl Subscriber<GameEvent> for HotSeat {
+	fn notify(&mut self, event: &GameEvent) {
+		let player = &mut self.players[self.current];
+		match event {
+			GameEvent::RevealTile(x, y, tile) if tile.state == TileState::Empty => {
+				// Openings take flags off the tiles they reveal
+				self.flag_owners.remove(&(*x, *y));
+				player.score += CLEAR_POINTS;
+				self.turn_taken = true;
+			}
+			GameEvent::Detonate(_, _, _) | GameEvent::Lose(_, _, _) => {
+				match self.mine_rule {
+					MineRule::Penalty => player.score -= MINE_PENALTY,
+					MineRule::Eliminate => player.eliminated = true,
+				}
+				self.turn_taken = true;
+			}
+			// The tile is as it was before the change, so a tile without a flag is getting one
+			GameEvent::FlagTile(x, y, tile) if tile.modifier != Some(TileModifier::Flagged) => {
+				self.flag_owners.insert((*x, *y), self.current);
+			}
+			GameEvent::FlagTile(x, y, _) | GameEvent::QuestionTile(x, y, _) => {
+				self.flag_owners.remove(&(*x, *y));
+			}
+			GameEvent::GameEnd(board) => {
+				for (&(x, y), &owner) in self.flag_owners.iter() {
+					let correct = board.get_tile(x, y).is_some_and(|tile| tile.state == TileState::Mine);
+					self.players[owner].score += if correct { FLAG_POINTS } else { -FLAG_POINTS };
+				}
+				self.flag_owners.clear();
+			}
+			_ => (),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::logic::{game_board::GameBoard, GameState, Minesweeper};
+
+	// Every other tile a mine, so no reveal floods into the next
+	const BOARD: &str = "6x1\n*.*.*.\n";
+
+	fn game(mine_rule: MineRule) -> Minesweeper {
+		let mut game = Minesweeper::default();
+		game.load_board(GameBoard::from_text(BOARD).unwrap());
+		game.set_hot_seat(Some(HotSeat::new(2, mine_rule)));
+		game
+	}
+
+	fn scores(game: &Minesweeper) -> Vec<isize> {
+		game.hot_seat.as_ref().unwrap().players.iter().map(|player| player.score).collect()
+	}
+
+	fn current(game: &Minesweeper) -> usize {
+		game.hot_seat.as_ref().unwrap().current
+	}
+
+	fn flagged(game: &Minesweeper, x: usize) -> bool {
+		game.board.get_tile(x, 0).unwrap().modifier == Some(TileModifier::Flagged)
+	}
+
+	#[test]
+	fn mines_cost_points_and_flags_score_at_the_end() {
+		let mut game = game(MineRule::Penalty);
+		game.reveal(1, 0);
+		assert_eq!((scores(&game), current(&game)), (vec![CLEAR_POINTS, 0], 1));
+
+		// Flags don't take a turn
+		game.modify(2, 0);
+		assert_eq!(current(&game), 1);
+		game.reveal(3, 0);
+		assert_eq!((scores(&game), current(&game)), (vec![CLEAR_POINTS, CLEAR_POINTS], 0));
+
+		// Only the player who placed a flag can take it away
+		game.modify(2, 0);
+		assert!(flagged(&game, 2));
+		game.modify(4, 0);
+		game.reveal(0, 0);
+		assert!(game.state == GameState::Playing);
+		assert_eq!(
+			(scores(&game), current(&game)),
+			(vec![CLEAR_POINTS - MINE_PENALTY, CLEAR_POINTS], 1)
+		);
+
+		game.reveal(5, 0);
+		assert!(game.state == GameState::Victory);
+		assert_eq!(
+			scores(&game),
+			vec![CLEAR_POINTS - MINE_PENALTY + FLAG_POINTS, 2 * CLEAR_POINTS + FLAG_POINTS]
+		);
+	}
+
+	#[test]
+	fn players_who_hit_a_mine_are_skipped_until_the_last_one_ends_the_game() {
+		let mut game = game(MineRule::Eliminate);
+		game.reveal(1, 0);
+		game.modify(3, 0);
+		assert!(game.hot_seat.as_ref().unwrap().survives_mine());
+		game.reveal(0, 0);
+		assert!(game.state == GameState::Playing);
+		assert!(game.hot_seat.as_ref().unwrap().players[1].eliminated);
+		assert_eq!(current(&game), 0);
+
+		// The eliminated player's flag stays theirs
+		game.modify(3, 0);
+		assert!(flagged(&game, 3));
+		game.reveal(5, 0);
+		assert_eq!((scores(&game), current(&game)), (vec![2 * CLEAR_POINTS, 0], 0));
+
+		assert!(!game.hot_seat.as_ref().unwrap().survives_mine());
+		game.reveal(2, 0);
+		assert!(game.state == GameState::GameOver);
+		assert_eq!(scores(&game), vec![2 * CLEAR_POINTS, -FLAG_POINTS]);
+	}
+}
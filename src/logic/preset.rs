@@ -0,0 +1,41 @@
+use std::{error::Error, str::FromStr};
+
+// The standard board sizes
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Preset {
+	Beginner,
+	Intermediate,
+	Expert,
+}
+
+impl Preset {
+	pub const ALL: [Preset; 3] = [Preset::Beginner, Preset::Intermediate, Preset::Expert];
+
+	// Width, height and mine count
+	pub fn board(self) -> (usize, usize, usize) {
+		match self {
+			Preset::Beginner => (9, 9, 10),
+			Preset::Intermediate => (16, 16, 40),
+			Preset::Expert => (30, 16, 99),
+		}
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Preset::Beginner => "beginner",
+			Preset::Intermediate => "intermediate",
+			Preset::Expert => "expert",
+		}
+	}
+}
+
+impl FromStr for Preset {
+	type Err = Box<dyn Error>;
+
+	fn from_str(name: &str) -> Result<Self, Self::Err> {
+		Preset::ALL
+			.into_iter()
+			.find(|preset| preset.name().eq_ignore_ascii_case(name))
+			.ok_or_else(|| format!("Unknown preset '{}', expected beginner, intermediate or expert", name).into())
+	}
+}
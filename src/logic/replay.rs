@@ -0,0 +1,379 @@
+use std::error::Error;
+
+use super::{
+	first_click::FirstClick,
+	game_board::{FlagRules, GameBoard, ModifyMode},
+	Minesweeper,
+};
+
+// Board squares are 16px in the original game, which is what RAWVF mouse positions are measured in.
+const RAWVF_SQUARE_SIZE: usize = 16;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ReplayAction {
+	Reveal,
+	Modify,
+	Chord,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct ReplayEvent {
+	pub time: f64,
+	pub action: ReplayAction,
+	pub x: usize,
+	pub y: usize,
+}
+
+#[derive(Clone)]
+pub struct Replay {
+	pub board: GameBoard,
+	pub events: Vec<ReplayEvent>,
+	pub question_marks: bool,
+	// The first click rule the game was played under, which replays from other programs don't give
+	pub first_click: Option<FirstClick>,
+	pub time: Option<f64>,
+}
+
+impl Replay {
+	// Reads the subset of the RAWVF (Rev2) text replay format needed to reconstruct a game:
+	// the header, the `Board:` section and the mouse events of the `Events:` section.
+	// Left release reveals, right press flags and middle release chords, matching how the
+	// original game reacts to the mouse. Mouse movement and other events are skipped.
+	pub fn from_rawvf(text: &str) -> Result<Self, Box<dyn Error>> {
+		#[derive(PartialEq)]
+		enum Section {
+			Header,
+			Board,
+			Events,
+		}
+		let mut section = Section::Header;
+		let mut width: Option<usize> = None;
+		let mut height: Option<usize> = None;
+		let mut question_marks = false;
+		let mut first_click = None;
+		let mut time = None;
+		let mut rows: Vec<&str> = vec![];
+		let mut events = vec![];
+
+		for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+			match line {
+				"Board:" => {
+					section = Section::Board;
+					continue;
+				}
+				"Events:" => {
+					section = Section::Events;
+					continue;
+				}
+				_ => (),
+			}
+			match section {
+				Section::Header => {
+					let Some((key, value)) = line.split_once(':') else {
+						continue;
+					};
+					let value = value.trim();
+					match key.trim() {
+						"Width" => width = Some(value.parse()?),
+						"Height" => height = Some(value.parse()?),
+						"Marks" => question_marks = value.eq_ignore_ascii_case("on"),
+						"First_Click" => first_click = value.parse().ok(),
+						"Time" => time = value.parse().ok(),
+						_ => (),
+					}
+				}
+				Section::Board => rows.push(line),
+				Section::Events => {
+					if let Some(event) = parse_rawvf_event(line)? {
+						events.push(event);
+					}
+				}
+			}
+		}
+
+		if rows.is_empty() {
+			return Err("Replay has no Board section".into());
+		}
+		let board_width = rows[0].chars().count();
+		let board_height = rows.len();
+		if width.unwrap_or(board_width) != board_width || height.unwrap_or(board_height) != board_height {
+			return Err("Replay Width and Height do not match its Board section".into());
+		}
+		let mut mines = vec![];
+		for (y, row) in rows.iter().enumerate() {
+			if row.chars().count() != board_width {
+				return Err(format!("Board row {} has the wrong length", y + 1).into());
+			}
+			for (x, symbol) in row.chars().enumerate() {
+				match symbol {
+					'*' => mines.push((x, y)),
+					'0' | '.' => (),
+					_ => return Err(format!("Unknown board symbol '{}' at row {}, column {}", symbol, y + 1, x + 1).into()),
+				}
+			}
+		}
+		for event in events.iter() {
+			if event.x >= board_width || event.y >= board_height {
+				return Err(format!("Event at square ({}, {}) is outside of the board", event.x + 1, event.y + 1).into());
+			}
+		}
+
+		Ok(Self {
+			board: GameBoard::from_mines(board_width, board_height, &mines)?,
+			events,
+			question_marks,
+			first_click,
+			time,
+		})
+	}
+
+	pub fn to_rawvf(&self) -> String {
+		let width = self.board.width();
+		let height = self.board.height();
+		let mines = self.board.mines();
+		let level = match (width, height, mines) {
+			(8, 8, 10) | (9, 9, 10) => "Beginner",
+			(16, 16, 40) => "Intermediate",
+			(30, 16, 99) => "Expert",
+			_ => "Custom",
+		};
+
+		let mut text = String::from("RawVF_Version: Rev2\n");
+		text += "Program: Rust Minesweeper\n";
+		text += &format!("Level: {}\n", level);
+		text += &format!("Width: {}\nHeight: {}\nMines: {}\n", width, height, mines);
+		text += &format!("Marks: {}\n", if self.question_marks { "On" } else { "Off" });
+		if let Some(first_click) = self.first_click {
+			text += &format!("First_Click: {}\n", first_click.name());
+		}
+		if let Some(time) = self.time {
+			text += &format!("Time: {:.3}\n", time);
+		}
+
+		text += "Board:\n";
+		for y in 0..height {
+			for x in 0..width {
+				text.push(if self.board.is_mine(x, y) { '*' } else { '0' });
+			}
+			text.push('\n');
+		}
+
+		text += "Events:\n0.000 start\n";
+		for event in self.events.iter() {
+			let (press, release) = match event.action {
+				ReplayAction::Reveal => ("lc", "lr"),
+				ReplayAction::Modify => ("rc", "rr"),
+				ReplayAction::Chord => ("mc", "mr"),
+			};
+			let position = format!(
+				"{} {} ({} {})",
+				event.x + 1,
+				event.y + 1,
+				event.x * RAWVF_SQUARE_SIZE + RAWVF_SQUARE_SIZE / 2,
+				event.y * RAWVF_SQUARE_SIZE + RAWVF_SQUARE_SIZE / 2
+			);
+			text += &format!("{:.3} {} {}\n", event.time, press, position);
+			text += &format!("{:.3} {} {}\n", event.time, release, position);
+		}
+		text
+	}
+}
+
+// Event lines look like `1.234 lr 5 3 (72 40)`, with the square given as a 1-based column and row.
+fn parse_rawvf_event(line: &str) -> Result<Option<ReplayEvent>, Box<dyn Error>> {
+	let mut parts = line.split_whitespace();
+	let (Some(time), Some(kind)) = (parts.next(), parts.next()) else {
+		return Ok(None);
+	};
+	let action = match kind {
+		"lr" => ReplayAction::Reveal,
+		"rc" => ReplayAction::Modify,
+		"mr" => ReplayAction::Chord,
+		_ => return Ok(None),
+	};
+	let (Some(column), Some(row)) = (parts.next(), parts.next()) else {
+		return Err(format!("Event is missing its square: {}", line).into());
+	};
+	let column: usize = column.parse()?;
+	let row: usize = row.parse()?;
+	if column == 0 || row == 0 {
+		return Err(format!("Event squares start at 1: {}", line).into());
+	}
+	Ok(Some(ReplayEvent {
+		time: time.parse()?,
+		action,
+		x: column - 1,
+		y: row - 1,
+	}))
+}
+
+// Steps a game through a replay's events as time passes.
+pub struct ReplayPlayer {
+	events: Vec<ReplayEvent>,
+	next: usize,
+	// How the replay's right clicks change markers, which is swapped in only while they are played back
+	modify_mode: ModifyMode,
+}
+
+impl ReplayPlayer {
+	// Loads the replay's board into the game, ready for the events to be played back.
+	pub fn start(replay: Replay, game: &mut Minesweeper) -> Self {
+		// Replays are of one player's game
+		game.hot_seat = None;
+		game.load_board(replay.board);
+		Self {
+			events: replay.events,
+			next: 0,
+			modify_mode: if replay.question_marks {
+				ModifyMode::Question
+			} else {
+				ModifyMode::Flag
+			},
+		}
+	}
+
+	// Applies every event which happened before `time` seconds into the replay.
+	pub fn update(&mut self, game: &mut Minesweeper, time: f64) {
+		let modify_mode = std::mem::replace(&mut game.board.modify_mode, self.modify_mode.clone());
+		// Replays hold every flag as it was placed, so the rules which place or refuse flags are left out of playing them.
+		// `Minesweeper::flag_obvious` records its flags as right clicks on the understanding that they're replayed this way.
+		let flag_rules = game.board.flag_rules;
+		game.board.flag_rules = FlagRules {
+			flag_on_win: flag_rules.flag_on_win,
+			..FlagRules::default()
+		};
+		while let Some(event) = self.events.get(self.next) {
+			if event.time > time {
+				break;
+			}
+			match event.action {
+				ReplayAction::Reveal => game.reveal(event.x, event.y),
+				ReplayAction::Modify => game.modify(event.x, event.y),
+				ReplayAction::Chord => game.chord(event.x, event.y),
+			}
+			self.next += 1;
+		}
+		game.board.flag_rules = flag_rules;
+		game.board.modify_mode = modify_mode;
+	}
+
+	pub fn is_finished(&self) -> bool {
+		self.next >= self.events.len()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::logic::visible::VisibleTile;
+
+	// Every tile as the player sees it, row by row
+	fn visible_tiles(game: &Minesweeper) -> Vec<VisibleTile> {
+		let board = &game.board;
+		(0..board.height())
+			.flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+			.map(|(x, y)| board.get_tile(x, y).unwrap().visible())
+			.collect()
+	}
+
+	fn moves(replay: &Replay) -> Vec<(ReplayAction, usize, usize)> {
+		replay.events.iter().map(|event| (event.action, event.x, event.y)).collect()
+	}
+
+	#[test]
+	fn played_games_round_trip() {
+		let mut game = Minesweeper::new(16, 16, 40, FirstClick::default()).unwrap();
+		game.board.seed = Some(5);
+		game.reveal(8, 8);
+		let hidden = visible_tiles(&game).iter().position(|&tile| tile == VisibleTile::Hidden).unwrap();
+		game.modify(hidden % 16, hidden / 16);
+		let replay = game.replay().unwrap();
+
+		let read = Replay::from_rawvf(&replay.to_rawvf()).unwrap();
+		assert_eq!(read.board.to_text(), replay.board.to_text());
+		assert_eq!(
+			moves(&read),
+			[(ReplayAction::Reveal, 8, 8), (ReplayAction::Modify, hidden % 16, hidden / 16)]
+		);
+		assert_eq!(read.question_marks, replay.question_marks);
+		assert_eq!(read.first_click, Some(FirstClick::Opening));
+		assert!(read.time.is_some());
+		assert_eq!(read.to_rawvf(), replay.to_rawvf());
+
+		// Playing it back ends up where the game was
+		let mut played = Minesweeper::default();
+		let mut player = ReplayPlayer::start(read, &mut played);
+		player.update(&mut played, f64::MAX);
+		assert!(player.is_finished());
+		assert_eq!(visible_tiles(&played), visible_tiles(&game));
+	}
+
+	#[test]
+	fn other_programs_replays_are_read() {
+		let text = "\
+RawVF_Version: Rev2
+Program: Viennasweeper
+Level: Custom
+Width: 3
+Height: 2
+Mines: 1
+Marks: On
+Time: 1.500
+Board:
+*00
+000
+Events:
+0.000 start
+0.100 mv 2 2 (24 24)
+0.200 lc 3 2 (40 24)
+0.300 lr 3 2 (40 24)
+0.400 rc 1 1 (8 8)
+0.500 rr 1 1 (8 8)
+0.600 mc 2 1 (24 8)
+0.700 mr 2 1 (24 8)
+";
+		let replay = Replay::from_rawvf(text).unwrap();
+		assert_eq!(replay.board.to_text(), "3x2\n*..\n...\n");
+		assert_eq!(
+			moves(&replay),
+			[
+				(ReplayAction::Reveal, 2, 1),
+				(ReplayAction::Modify, 0, 0),
+				(ReplayAction::Chord, 1, 0)
+			]
+		);
+		assert_eq!(replay.events[0].time, 0.3);
+		assert!(replay.question_marks);
+		assert_eq!(replay.first_click, None);
+		assert_eq!(replay.time, Some(1.5));
+		let written = replay.to_rawvf();
+		assert!(!written.contains("First_Click"));
+		assert_eq!(moves(&Replay::from_rawvf(&written).unwrap()), moves(&replay));
+	}
+
+	#[test]
+	fn bad_replays_are_refused() {
+		let with_events = |events: &str| format!("Width: 2\nHeight: 1\nBoard:\n*0\nEvents:\n{}\n", events);
+		let bad = [
+			("Width: 2\nHeight: 1\n".to_string(), "Replay has no Board section"),
+			(
+				"Width: 3\nHeight: 1\nBoard:\n*0\n".to_string(),
+				"Replay Width and Height do not match its Board section",
+			),
+			("Board:\n*0\n000\n".to_string(), "Board row 2 has the wrong length"),
+			("Board:\n*x\n".to_string(), "Unknown board symbol 'x' at row 1, column 2"),
+			(with_events("0.1 lr 3 1"), "Event at square (3, 1) is outside of the board"),
+			(with_events("0.1 lr 2"), "Event is missing its square: 0.1 lr 2"),
+			(with_events("0.1 lr 0 1"), "Event squares start at 1: 0.1 lr 0 1"),
+			("Board:\n**\n".to_string(), "Board must have at least one safe tile"),
+		];
+		for (text, error) in bad {
+			assert_eq!(
+				Replay::from_rawvf(&text).err().map(|error| error.to_string()).as_deref(),
+				Some(error)
+			);
+		}
+		assert!(Replay::from_rawvf(&with_events("soon lr 1 1")).is_err());
+		assert!(Replay::from_rawvf("Width: wide\nBoard:\n*0\n").is_err());
+	}
+}
@@ -1,3 +1,7 @@
+use std::fmt;
+
+use super::visible::VisibleTile;
+
 #[derive(Copy, Clone, PartialEq, Default)]
 pub enum TileState {
 	#[default]
@@ -10,20 +14,27 @@ pub enum TileModifier {
 	Unsure,
 }
 
+// What a tile holds is only known to the game itself. Everything else sees it through `visible`, or once the game
+// is over, through `GameBoard::shown_mine`.
 #[derive(Copy, Clone, Default)]
 pub struct Tile {
-	pub state: TileState,
+	pub(super) state: TileState,
 	pub modifier: Option<TileModifier>,
 	pub swept: bool,
-	pub adjacent: u8,
-	pub safe: bool,
+	pub(super) adjacent: u8,
+	pub(super) safe: bool,
 	pub highlighted: bool,
 }
 
 impl Tile {
-	pub fn increment_adjacent(&mut self) {
-		if self.state == TileState::Empty {
-			self.adjacent += 1;
+	// The tile as the player sees it
+	pub fn visible(&self) -> VisibleTile {
+		match (self.swept, self.state, self.modifier) {
+			(true, TileState::Mine, _) => VisibleTile::Exploded,
+			(true, TileState::Empty, _) => VisibleTile::Revealed(self.adjacent),
+			(false, _, Some(TileModifier::Flagged)) => VisibleTile::Flagged,
+			(false, _, Some(TileModifier::Unsure)) => VisibleTile::Questioned,
+			(false, _, None) => VisibleTile::Hidden,
 		}
 	}
 	pub fn highlight(&mut self) {
@@ -35,3 +46,10 @@ impl Tile {
 		self.highlighted = false;
 	}
 }
+
+// Only shows what the player can see, so printing a tile never gives away a mine.
+impl fmt::Display for Tile {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.visible())
+	}
+}
@@ -1,4 +1,7 @@
-use macroquad::time::get_time;
+use macroquad::miniquad::date;
+
+// Measured with miniquad's clock, which needs no window but still works on the web, unlike `std::time::Instant`.
+// Times are in seconds.
 #[derive(Default)]
 pub struct Timer {
 	start_time: Option<f64>,
@@ -19,14 +22,24 @@ impl Timer {
 		self.state = TimerState::Stopped;
 	}
 	pub fn start(&mut self) {
-		self.start_time = Some(get_time());
+		self.start_time = Some(date::now());
 		self.state = TimerState::Running;
 	}
 	pub fn elapsed(&self) -> Option<f64> {
 		if let TimerState::Frozen = self.state {
 			return Some(self.old);
 		}
-		self.start_time.map(|time| get_time() - time)
+		self.start_time.map(|time| date::now() - time)
+	}
+	// Picks up the time of a game measured elsewhere
+	pub fn set(&mut self, elapsed: f64, running: bool) {
+		if running {
+			self.start_time = Some(date::now() - elapsed);
+			self.state = TimerState::Running;
+		} else {
+			self.old = elapsed;
+			self.state = TimerState::Frozen;
+		}
 	}
 	pub fn stop(&mut self) {
 		self.old = self.elapsed().unwrap_or(0f64);
@@ -0,0 +1,83 @@
+use crate::util::ADJACENT_WITHOUT_CENTER;
+
+use super::{game_board::GameBoard, tile::Tile};
+
+use std::fmt;
+
+// A tile as the player sees it, without anything they aren't meant to know
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum VisibleTile {
+	Hidden,
+	Flagged,
+	Questioned,
+	// A revealed tile and its number of adjacent mines
+	Revealed(u8),
+	// A revealed mine
+	Exploded,
+}
+
+// `.` for a hidden tile, `F` and `?` for its markers, the number of a revealed tile and `*` for a mine set off
+impl fmt::Display for VisibleTile {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			VisibleTile::Hidden => write!(f, "."),
+			VisibleTile::Flagged => write!(f, "F"),
+			VisibleTile::Questioned => write!(f, "?"),
+			VisibleTile::Revealed(adjacent) => write!(f, "{}", adjacent),
+			VisibleTile::Exploded => write!(f, "*"),
+		}
+	}
+}
+
+// What can be seen of a board: its size, mine count and the tiles as the player sees them. This is all a fair
+// player, such as a bot, gets to work with.
+#[derive(Clone, Debug)]
+pub struct VisibleBoard {
+	width: usize,
+	height: usize,
+	mines: usize,
+	tiles: Vec<VisibleTile>,
+}
+
+impl VisibleBoard {
+	pub fn mines(&self) -> usize {
+		self.mines
+	}
+
+	pub fn get(&self, x: usize, y: usize) -> Option<VisibleTile> {
+		if x < self.width && y < self.height {
+			return Some(self.tiles[y * self.width + x]);
+		}
+		None
+	}
+
+	// Every tile on the board with its position, row by row
+	pub fn tiles(&self) -> impl Iterator<Item = (usize, usize, VisibleTile)> + '_ {
+		self.tiles.iter().enumerate().map(|(i, &tile)| (i % self.width, i / self.width, tile))
+	}
+
+	// Positions of the tiles around a tile which are on the board
+	pub fn neighbours(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+		ADJACENT_WITHOUT_CENTER.iter().filter_map(move |&(offset_x, offset_y)| {
+			let (x, y) = (x.checked_add_signed(offset_x)?, y.checked_add_signed(offset_y)?);
+			(x < self.width && y < self.height).then_some((x, y))
+		})
+	}
+}
+
+impl From<&GameBoard> for VisibleBoard {
+	fn from(board: &GameBoard) -> Self {
+		let mut tiles = Vec::with_capacity(board.width() * board.height());
+		for y in 0..board.height() {
+			for x in 0..board.width() {
+				tiles.push(board.get_tile(x, y).map_or(VisibleTile::Hidden, Tile::visible));
+			}
+		}
+		Self {
+			width: board.width(),
+			height: board.height(),
+			mines: board.mines(),
+			tiles,
+		}
+	}
+}
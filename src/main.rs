@@ -1,37 +1,132 @@
-use gui::top_menu::smile::SmileyState;
-use gui::{GUIEvent, GameUI, UIState};
-use logic::{GameEvent, Minesweeper};
+use cli::{Command, Options};
+use gui::{
+	endless_view::ENDLESS_VIEW_SIZE,
+	frame_bench::{FrameBench, BENCH_BOARD},
+	localization::LANGUAGE_DIRECTORY,
+	sound::SoundPlayer,
+	texture_store::{DEFAULT_TILES, TILE_LAYOUT},
+	theme::{Theme, THEME_DIRECTORY},
+	top_menu::Counter,
+	GUIEvent, GameUI, UIState,
+};
+use logic::{
+	endless::EndlessGame,
+	event_log::EventLog,
+	game_board::GameBoard,
+	hot_seat::HotSeat,
+	replay::{Replay, ReplayPlayer},
+	GameState, Minesweeper,
+};
 use macroquad::{
+	miniquad::conf::Platform,
 	prelude::*,
 	ui::{root_ui, Skin},
 	Window,
 };
-mod gui;
-mod logic;
-mod sprite_loader;
-mod util;
+use minesweeper::{cli, gui, logic, net, snapshot, sprite_loader::sheet_layout};
+use net::{
+	broadcast::Broadcast,
+	coop::{CoopClient, Intent},
+	race::Race,
+};
+use std::path::Path;
 
 fn main() {
-	let width = 30 * 32;
-	let height = 16 * 32 + 100;
+	let options = match Options::parse(std::env::args().skip(1)) {
+		Ok(options) => options,
+		Err(error) => {
+			eprintln!("{}, see --help", error);
+			return;
+		}
+	};
+	if options.command != Command::Play {
+		if let Err(error) = options.run_command() {
+			eprintln!("{}", error);
+		}
+		return;
+	}
+	let (width, height) = options.window.unwrap_or((30 * 32, 16 * 32 + 100));
 	Window::from_config(
 		Conf {
 			sample_count: 2,
 			window_title: String::from("Minesweeper"),
-			high_dpi: false,
+			high_dpi: true,
 			window_width: width,
 			window_height: height,
+			platform: Platform {
+				// Benchmarks measure frames as fast as they can be drawn, rather than at the refresh rate
+				swap_interval: options.frame_bench.map(|_| 0),
+				..Default::default()
+			},
 			..Default::default()
 		},
-		run(),
+		run(options),
 	);
 }
 
-async fn run() {
-	let mut game_logic = Minesweeper::new(30, 16, 99).unwrap();
+// Skin for the settings menu, drawing text with `font` when given a TTF, else with the default font.
+fn build_settings_skin(font: Option<&[u8]>) -> Skin {
+	let button_texture = Image::from_file_with_format(include_bytes!("../assets/button.png"), Some(ImageFormat::Png));
+	let button_clicked_texture =
+		Image::from_file_with_format(include_bytes!("../assets/button_clicked.png"), Some(ImageFormat::Png));
+	let style_builder = || {
+		let builder = root_ui().style_builder().font_size(20);
+		match font.map(|font| root_ui().style_builder().font_size(20).font(font)) {
+			Some(Ok(font_builder)) => font_builder,
+			Some(Err(error)) => {
+				eprintln!("Could not load font: {:?}", error);
+				builder
+			}
+			None => builder,
+		}
+	};
+	let label_style = style_builder().build();
+	let button_style = style_builder().background(button_texture).background_clicked(button_clicked_texture).build();
+	let editbox_style = style_builder().build();
+	Skin {
+		scroll_width: 0f32,
+		button_style,
+		label_style,
+		editbox_style,
+		..root_ui().default_skin()
+	}
+}
+
+async fn run(options: Options) {
+	let mut frame_bench = options.frame_bench.map(FrameBench::new);
+	let (width, height, mines) = if frame_bench.is_some() { BENCH_BOARD } else { options.board() };
+	let mut game_logic = match Minesweeper::new(width, height, mines, options.first_click) {
+		Ok(game) => game,
+		Err(error) => {
+			eprintln!("Could not start a {}x{} game with {} mines: {}", width, height, mines, error);
+			return;
+		}
+	};
+	game_logic.board.seed = options.seed;
+	// The player's rule, which races and co-op games put aside for their own
+	let mut first_click = options.first_click;
+	if frame_bench.is_some() {
+		game_logic.reveal(width / 2, height / 2);
+	}
 
 	let top_buffer = 100; //px
-	let mut interface = GameUI::new(UIState::new(30, 16, 32, top_buffer));
+	let mut interface = GameUI::new(UIState::new(width, height, 32, top_buffer));
+	interface.settings_menu.set_themes(Theme::discover(Path::new(THEME_DIRECTORY)));
+	interface.localization.load_directory(Path::new(LANGUAGE_DIRECTORY));
+	interface.settings_menu.set_board(width, height, mines);
+	interface.settings_menu.set_first_click(options.first_click);
+	if let Some(theme) = options.theme {
+		interface.event_handler.add(GUIEvent::SwitchTheme(Some(theme)));
+	}
+	if let Some(path) = options.open {
+		let is_replay = path.extension().is_some_and(|extension| extension == "rawvf");
+		let path = path.to_string_lossy().into_owned();
+		interface.event_handler.add(if is_replay {
+			GUIEvent::LoadReplay(path)
+		} else {
+			GUIEvent::LoadBoard(path)
+		});
+	}
 
 	let skin = {
 		let button_style = root_ui().style_builder().build();
@@ -46,24 +141,7 @@ async fn run() {
 		}
 	};
 
-	let settings_skin = {
-		let button_texture = Image::from_file_with_format(include_bytes!("../assets/button.png"), Some(ImageFormat::Png));
-		let button_clicked_texture =
-			Image::from_file_with_format(include_bytes!("../assets/button_clicked.png"), Some(ImageFormat::Png));
-		let label_style = root_ui().style_builder().font_size(20).build();
-		let button_style = root_ui()
-			.style_builder()
-			.font_size(20)
-			.background(button_texture)
-			.background_clicked(button_clicked_texture)
-			.build();
-		Skin {
-			scroll_width: 0f32,
-			button_style,
-			label_style,
-			..root_ui().default_skin()
-		}
-	};
+	let mut settings_skin = build_settings_skin(None);
 	let settings_skin_exit = {
 		let button_texture = Image::from_file_with_format(include_bytes!("../assets/exit_button.png"), Some(ImageFormat::Png));
 		let button_hover_texture =
@@ -80,30 +158,123 @@ async fn run() {
 		}
 	};
 
+	let mut event_log = EventLog::new(options.log_events);
+	let mut sound_player = SoundPlayer::load(1.0).await;
+	let mut replay_player: Option<(ReplayPlayer, f64)> = None;
+	// Set while playing the endless mode, which takes the place of the normal game
+	let mut endless_game: Option<EndlessGame> = None;
+	// Set while racing another player over the network
+	let mut race = match &options.race {
+		Some(address) => match Race::join(address) {
+			Ok(race) => {
+				println!("Waiting for an opponent");
+				Some(race)
+			}
+			Err(error) => {
+				eprintln!("Could not join race: {}", error);
+				None
+			}
+		},
+		None => None,
+	};
+	let mut race_reported = false;
+	// Set while sharing a board with other players, whose server decides what every click does, or while
+	// watching someone else's game
+	let mut coop = match (&options.coop, &options.spectate) {
+		(Some(address), _) => match CoopClient::join(address) {
+			Ok(coop) => Some(coop),
+			Err(error) => {
+				eprintln!("Could not join shared board: {}", error);
+				None
+			}
+		},
+		(_, Some(address)) => match CoopClient::spectate(address) {
+			Ok(spectator) => Some(spectator),
+			Err(error) => {
+				eprintln!("Could not watch broadcast: {}", error);
+				None
+			}
+		},
+		_ => None,
+	};
+	// Set while streaming this game to spectators
+	let mut broadcast = match &options.broadcast {
+		Some(address) => match Broadcast::listen(address) {
+			Ok(broadcast) => Some(broadcast),
+			Err(error) => {
+				eprintln!("Could not broadcast game: {}", error);
+				None
+			}
+		},
+		None => None,
+	};
 	let mut old_screen_size = (0.0, 0.0);
 	let background_color = Color::from_rgba(123, 123, 123, 255);
 	loop {
+		if let Some(bench) = &mut frame_bench {
+			bench.begin_frame();
+		}
 		root_ui().push_skin(&skin);
 		clear_background(background_color);
 
-		while let Some(ge) = game_logic.events.next() {
-			match ge {
-				GameEvent::Lose(_, _, _) => {
-					interface.state.frozen = true;
-					interface.event_handler.add(GUIEvent::SetSmileyState(SmileyState::Dead));
-					interface.state.reveal_all = true;
-				}
-				GameEvent::Win => {
-					interface.state.frozen = true;
-					interface.event_handler.add(GUIEvent::SetSmileyState(SmileyState::Victory));
-					interface.state.reveal_all = true;
-				}
-				GameEvent::Reset => {
-					interface.clear();
+		if let Some(race) = &mut race {
+			if race.receive(&mut game_logic) {
+				endless_game = None;
+				interface.state.frozen = false;
+				interface.state.update_dimensions(game_logic.board.width(), game_logic.board.height());
+				interface.state.update_letterbox(screen_width(), screen_height());
+			}
+			// Nothing can be clicked until the relay has found an opponent
+			interface.state.frozen |= !race.has_started();
+			if race.is_over(&game_logic) && !race_reported {
+				println!("{}", race.summary(&game_logic));
+				race_reported = true;
+			}
+		}
+		if let Some(coop) = &mut coop {
+			if coop.receive(&mut game_logic) {
+				if !coop.read_only {
+					println!("Joined the shared board as player {}", coop.player.unwrap_or_default() + 1);
 				}
-				_ => (),
+				replay_player = None;
+				endless_game = None;
+				interface.state.frozen = false;
+				interface.state.update_dimensions(game_logic.board.width(), game_logic.board.height());
+				interface.state.update_letterbox(screen_width(), screen_height());
+			}
+			if interface.state.mouse_in_minefield {
+				coop.send_cursor(interface.state.cursor.0, interface.state.cursor.1);
+			}
+			// Spectators only watch
+			interface.state.frozen |= coop.read_only;
+		}
+		if let Some(broadcast) = &mut broadcast {
+			let cursor = interface.state.mouse_in_minefield.then_some(interface.state.cursor);
+			broadcast.update(&game_logic, cursor);
+		}
+		if coop.as_ref().is_some_and(|coop| !coop.is_open()) {
+			eprintln!("Lost the shared board");
+			coop = None;
+			game_logic.board.first_click = first_click;
+		}
+		match &mut race {
+			Some(race) => game_logic.events.dispatch(&mut [&mut interface, &mut sound_player, &mut event_log, race]),
+			None => game_logic.events.dispatch(&mut [&mut interface, &mut sound_player, &mut event_log]),
+		}
+		if let Some(endless) = &mut endless_game {
+			endless.events.dispatch(&mut [&mut interface, &mut sound_player]);
+		}
+		sound_player.play_queued();
+		if let Some((player, start_time)) = &mut replay_player {
+			// Input is blocked until the replay is done so it can't diverge from the recording.
+			interface.state.frozen = true;
+			player.update(&mut game_logic, get_time() - *start_time);
+			if player.is_finished() {
+				interface.state.frozen = !matches!(game_logic.state, GameState::Playing | GameState::Empty);
+				replay_player = None;
 			}
 		}
+		interface.state.dpi_scale = gui::screen_dpi_scale();
 		{
 			let screen_width = screen_width();
 			let screen_height = screen_height();
@@ -112,6 +283,12 @@ async fn run() {
 				interface.state.update_letterbox(screen_width, screen_height);
 				old_screen_size = (screen_width, screen_height);
 			}
+			// Zooming and panning, with the minimap and dragging taking the mouse away from the board
+			let mut mouse_captured = endless_game.is_some();
+			if !interface.state.settings_open && endless_game.is_none() {
+				interface.state.update_camera();
+				mouse_captured = interface.minimap.events(&mut interface.state) || interface.state.camera.is_panning();
+			}
 
 			let (mouse_x, mouse_y) = mouse_position();
 			let (min_x, min_y) = interface.state.pixel_screen_offset(10, 10 + interface.state.top_offset);
@@ -121,7 +298,7 @@ async fn run() {
 				interface.state.height * tile_size + interface.state.top_offset - 10,
 			);
 
-			if mouse_x < min_x || mouse_y < min_y || mouse_x > max_x || mouse_y > max_y {
+			if mouse_captured || mouse_x < min_x || mouse_y < min_y || mouse_x > max_x || mouse_y > max_y {
 				interface.state.mouse_in_minefield = false;
 			} else {
 				interface.state.mouse_in_minefield = true;
@@ -134,36 +311,106 @@ async fn run() {
 			}
 			if interface.state.settings_open {
 				interface.settings_menu.render(
-					&interface.state,
+					&interface.localization,
 					&mut interface.event_handler,
 					&interface.texture_store,
 					&settings_skin,
 					&settings_skin_exit,
 				);
+			} else if let Some(endless) = &endless_game {
+				interface.endless_view.update(endless, &interface.state, &mut interface.event_handler);
+				interface.endless_view.render(endless, &interface.texture_store, &interface.state);
+				interface.top_menu.render(
+					&interface.state,
+					endless.get_time(),
+					Counter::Single(endless.score() as isize),
+					&mut interface.event_handler,
+					&interface.texture_store,
+				);
 			} else {
 				interface.highlighter.events(&interface.state, &mut interface.event_handler, &mut game_logic.board);
 				interface.highlighter.highlight(&interface.state, &mut interface.event_handler);
-				game_logic.board.render(&interface.texture_store, &interface.state);
+				// Playing for someone racing would be cheating
+				if race.is_none() {
+					interface.autoplay.update(&game_logic, &interface.state, &mut interface.event_handler);
+				}
+				interface.animator.update();
+				game_logic.board.render(&interface.texture_store, &interface.state, &interface.animator);
+				if let Some(race) = &race {
+					gui::race_view::render_ghost(race, &game_logic, &interface.state);
+				}
+				if let Some(coop) = &coop {
+					gui::coop_view::render(coop, &interface.state);
+				}
+				interface.autoplay.render(&interface.state);
+				interface.minimap.render(&game_logic.board, &interface.state);
 				game_logic.board.events(&interface.state, &mut interface.event_handler);
 				interface.top_menu.render(
 					&interface.state,
-					&game_logic,
+					game_logic.get_time(),
+					match &game_logic.hot_seat {
+						Some(hot_seat) => Counter::Players(hot_seat),
+						None => Counter::Single(game_logic.board.remaining_flags()),
+					},
 					&mut interface.event_handler,
 					&interface.texture_store,
 				);
+				if let Some(race) = &race {
+					gui::race_view::render_progress(race, &game_logic.board, &interface.state);
+				}
 			}
 		}
 
-		while let Some(ue) = interface.event_handler.next() {
+		while let Some(ue) = interface.event_handler.pop() {
+			// Starting any other kind of game leaves the race. A rule the board has no room for starts nothing.
+			let leaves_race = match ue {
+				GUIEvent::SetFirstClick(rule) => game_logic.board.has_room(rule),
+				_ => matches!(
+					ue,
+					GUIEvent::CreateNewGame(..)
+						| GUIEvent::LoadBoard(_)
+						| GUIEvent::LoadReplay(_)
+						| GUIEvent::SetHotSeat(..)
+						| GUIEvent::StartEndless
+				),
+			};
+			if race.is_some() && leaves_race {
+				race = None;
+				game_logic.board.seed = None;
+				game_logic.board.first_click = first_click;
+			}
+			if coop.is_some() && leaves_race {
+				coop = None;
+				game_logic.board.first_click = first_click;
+			}
 			match ue {
+				// Races can't be restarted, only left once they are over
+				GUIEvent::ClickReset if race.as_ref().is_some_and(|race| !race.is_over(&game_logic)) => {
+					eprintln!("The race isn't over yet");
+				}
+				GUIEvent::ClickReset if coop.is_some() => coop.as_mut().unwrap().send(Intent::Reset),
+				GUIEvent::ClickTile(x, y) if coop.is_some() => coop.as_mut().unwrap().send(Intent::Reveal(x, y)),
+				GUIEvent::ModifyTile(x, y) if coop.is_some() => coop.as_mut().unwrap().send(Intent::Modify(x, y)),
+				GUIEvent::QuestionTile(x, y) if coop.is_some() => coop.as_mut().unwrap().send(Intent::Question(x, y)),
+				GUIEvent::ChordTile(x, y) if coop.is_some() => coop.as_mut().unwrap().send(Intent::Chord(x, y)),
 				GUIEvent::ClickReset => {
-					game_logic.reset();
+					if race.take().is_some() {
+						game_logic.board.seed = None;
+						game_logic.board.first_click = first_click;
+					}
+					replay_player = None;
+					match &mut endless_game {
+						Some(endless) => *endless = EndlessGame::random(),
+						None => game_logic.reset(),
+					}
 					interface.state.mouse_in_minefield = false
 				}
 				GUIEvent::ClickTile(x, y) => {
 					game_logic.reveal(x, y);
 				}
 				GUIEvent::ModifyTile(x, y) => game_logic.modify(x, y),
+				GUIEvent::QuestionTile(x, y) => game_logic.question(x, y),
+				GUIEvent::ChordTile(x, y) => game_logic.chord(x, y),
 				GUIEvent::HighlightTile(x, y) => game_logic.highlight(x, y),
 				GUIEvent::UnHighlightTile(x, y) => game_logic.remove_highlight(x, y),
 				GUIEvent::OpenSettings => {
@@ -175,21 +422,148 @@ async fn run() {
 					interface.state.frozen = false;
 					interface.state.settings_open = false;
 				}
-				GUIEvent::SwitchLanguage(lang) => {
-					interface.state.language = lang;
-					interface.texture_store.lang = lang;
+				GUIEvent::SwitchLanguage(index) => {
+					interface.localization.set_language(index);
+					let language = interface.localization.current();
+					interface.texture_store.set_language_tiles(&language.tile_sheets);
+					settings_skin = build_settings_skin(language.font.as_deref());
 				}
+				GUIEvent::SwitchTheme(None) => interface.texture_store.set_theme(None),
+				GUIEvent::SwitchTheme(Some(path)) => match Theme::load(&path) {
+					Ok(theme) => interface.texture_store.set_theme(Some(&theme)),
+					Err(error) => eprintln!("Could not load theme from {}: {}", path.display(), error),
+				},
 				GUIEvent::CreateNewGame(width, height, mines) => {
+					replay_player = None;
+					endless_game = None;
 					interface.state.frozen = false;
-					interface.state.update_dimensions(width, height);
-					game_logic.update_and_reset(width, height, mines);
+					match game_logic.update_and_reset(width, height, mines) {
+						Ok(()) => interface.state.update_dimensions(width, height),
+						Err(error) => {
+							eprintln!("Could not start a {}x{} game with {} mines: {}", width, height, mines, error)
+						}
+					}
 					interface.state.update_letterbox(screen_width(), screen_height())
 				}
-				GUIEvent::SetQuestionMode(mode) => game_logic.board.modify_mode = mode,
+				GUIEvent::LoadBoard(path) => match std::fs::read_to_string(&path) {
+					Ok(text) => match GameBoard::from_text(&text) {
+						Ok(board) => {
+							replay_player = None;
+							endless_game = None;
+							interface.state.frozen = false;
+							interface.state.update_dimensions(board.width(), board.height());
+							game_logic.load_board(board);
+							interface.state.update_letterbox(screen_width(), screen_height());
+							interface.event_handler.add(GUIEvent::CloseSettings);
+						}
+						Err(error) => eprintln!("Could not load board from {}: {}", path, error),
+					},
+					Err(error) => eprintln!("Could not read {}: {}", path, error),
+				},
+				GUIEvent::SaveBoard(_) | GUIEvent::SaveImage(_) | GUIEvent::SaveReplay(_) if endless_game.is_some() => {
+					eprintln!("Endless fields can't be saved");
+				}
+				GUIEvent::SaveBoard(_) | GUIEvent::SaveReplay(_) if coop.is_some() => {
+					eprintln!("Only the server knows the whole shared board");
+				}
+				GUIEvent::SaveBoard(path) => match game_logic.board.shown_text() {
+					Some(text) => {
+						if let Err(error) = std::fs::write(&path, text) {
+							eprintln!("Could not save board to {}: {}", path, error);
+						}
+					}
+					None => eprintln!("The board can only be saved once its game is over"),
+				},
+				GUIEvent::SaveImage(path) => {
+					let reveal_all = interface.state.reveal_all;
+					let language = interface.localization.current();
+					// Images are saved with the tiles closest to the built in 32px size
+					let tile_sheet = language
+						.tile_sheets
+						.iter()
+						.min_by_key(|sheet| {
+							sheet_layout(sheet, TILE_LAYOUT.rows, TILE_LAYOUT.columns)
+								.map_or(u32::MAX, |layout| layout.tile_size.0.abs_diff(TILE_LAYOUT.tile_size.0))
+						})
+						.map_or(DEFAULT_TILES, Vec::as_slice);
+					if let Err(error) = snapshot::save_board_png(&game_logic.board, reveal_all, tile_sheet, &path) {
+						eprintln!("Could not save board image to {}: {}", path, error);
+					}
+				}
+				GUIEvent::LoadReplay(path) => match std::fs::read_to_string(&path) {
+					Ok(text) => match Replay::from_rawvf(&text) {
+						Ok(replay) => {
+							endless_game = None;
+							interface.state.update_dimensions(replay.board.width(), replay.board.height());
+							replay_player = Some((ReplayPlayer::start(replay, &mut game_logic), get_time()));
+							interface.state.update_letterbox(screen_width(), screen_height());
+							interface.event_handler.add(GUIEvent::CloseSettings);
+						}
+						Err(error) => eprintln!("Could not load replay from {}: {}", path, error),
+					},
+					Err(error) => eprintln!("Could not read {}: {}", path, error),
+				},
+				GUIEvent::SaveReplay(_) if game_logic.hot_seat.is_some() => {
+					eprintln!("Hot-seat games can't be saved as replays");
+				}
+				GUIEvent::SaveReplay(path) => match game_logic.replay() {
+					Some(replay) => {
+						if let Err(error) = std::fs::write(&path, replay.to_rawvf()) {
+							eprintln!("Could not save replay to {}: {}", path, error);
+						}
+					}
+					None => eprintln!("Nothing to save, the game has not started yet"),
+				},
+				GUIEvent::SetModifyMode(mode) => game_logic.board.modify_mode = mode,
+				GUIEvent::SetFlagRules(rules) => game_logic.board.flag_rules = rules,
+				GUIEvent::SetAnimations(enabled) => interface.animator.set_enabled(enabled),
+				GUIEvent::SetVolume(volume) => sound_player.set_volume(volume),
+				GUIEvent::SetAutoplay(speed) => interface.autoplay.set_speed(speed),
 				GUIEvent::SetSmileyState(smiley_state) => interface.top_menu.smile.set_smile(smiley_state),
+				GUIEvent::SetHotSeat(players, mine_rule) => {
+					replay_player = None;
+					game_logic.set_hot_seat((players > 1).then(|| HotSeat::new(players, mine_rule)));
+				}
+				GUIEvent::SetFirstClick(rule) => {
+					replay_player = None;
+					match game_logic.set_first_click(rule) {
+						Ok(()) => first_click = rule,
+						Err(error) => eprintln!("Could not change the first click rule: {}", error),
+					}
+					// The menu shows the rule being played, which a rejected change leaves as it was
+					interface.settings_menu.set_first_click(game_logic.board.first_click);
+				}
+				GUIEvent::StartEndless => {
+					replay_player = None;
+					interface.state.update_dimensions(ENDLESS_VIEW_SIZE.0, ENDLESS_VIEW_SIZE.1);
+					interface.state.update_letterbox(screen_width(), screen_height());
+					endless_game = Some(EndlessGame::random());
+				}
+				GUIEvent::EndlessReveal(x, y) => {
+					if let Some(endless) = &mut endless_game {
+						endless.reveal(x, y);
+					}
+				}
+				GUIEvent::EndlessModify(x, y) => {
+					if let Some(endless) = &mut endless_game {
+						endless.modify(x, y);
+					}
+				}
+				GUIEvent::EndlessChord(x, y) => {
+					if let Some(endless) = &mut endless_game {
+						endless.chord(x, y);
+					}
+				}
 			}
 		}
 
+		if let Some(bench) = &mut frame_bench {
+			bench.end_frame();
+			if bench.is_finished() {
+				println!("{}", bench.report());
+				return;
+			}
+		}
 		next_frame().await;
 	}
 }
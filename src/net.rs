@@ -0,0 +1,7 @@
+pub mod broadcast;
+mod connection;
+pub mod coop;
+pub mod coop_server;
+pub mod protocol;
+pub mod race;
+pub mod relay;
@@ -0,0 +1,104 @@
+use std::{
+	error::Error,
+	io::Write,
+	net::{TcpListener, TcpStream},
+	sync::mpsc::{self, Receiver},
+	thread,
+	time::Duration,
+};
+
+use crate::logic::Minesweeper;
+
+use super::{
+	coop::{self, CoopMessage},
+	protocol::DEFAULT_ADDRESS,
+};
+
+// The player broadcasts are streamed as, which picks the colour spectators see their cursor in
+const PLAYER: usize = 0;
+// Spectators who can't take messages this quickly are dropped rather than holding up the game
+const WRITE_TIMEOUT: Duration = Duration::from_millis(50);
+
+// Publishes a game as it is played, in the messages of a shared board, so spectators can follow it read-only.
+pub struct Broadcast {
+	joining: Receiver<TcpStream>,
+	spectators: Vec<TcpStream>,
+	// The board spectators were last told about, so they can be told when a new one starts
+	board: Option<(usize, usize, usize)>,
+	cursor: Option<(usize, usize)>,
+}
+
+impl Broadcast {
+	pub fn listen(address: &str) -> Result<Self, Box<dyn Error>> {
+		let address = if address.is_empty() { DEFAULT_ADDRESS } else { address };
+		let listener = TcpListener::bind(address)?;
+		let (sender, joining) = mpsc::channel();
+		thread::spawn(move || {
+			for stream in listener.incoming() {
+				match stream {
+					Ok(stream) => {
+						if sender.send(stream).is_err() {
+							return;
+						}
+					}
+					Err(error) => eprintln!("Could not accept spectator: {}", error),
+				}
+			}
+		});
+		println!("Broadcasting the game on {}", address);
+		Ok(Self {
+			joining,
+			spectators: vec![],
+			board: None,
+			cursor: None,
+		})
+	}
+
+	// Sends spectators the game's events which haven't been dispatched yet, and the tile under the cursor. Newly
+	// connected spectators are sent the whole game instead, so this needs to run before the events are dispatched.
+	pub fn update(&mut self, game: &Minesweeper, cursor: Option<(usize, usize)>) {
+		let board = (game.board.width(), game.board.height(), game.board.mines());
+		let mut messages = vec![];
+		if self.board != Some(board) {
+			self.board = Some(board);
+			self.cursor = None;
+			messages = welcome(game);
+		} else {
+			for event in game.events.iter() {
+				messages.extend(coop::event_messages(event, game, PLAYER));
+			}
+		}
+		if let Some((x, y)) = cursor.filter(|&cursor| self.cursor != Some(cursor)) {
+			self.cursor = cursor;
+			messages.push(CoopMessage::Cursor(PLAYER, x, y));
+		}
+		self.spectators.retain_mut(|spectator| send(spectator, &messages));
+
+		while let Ok(mut spectator) = self.joining.try_recv() {
+			let _ = spectator.set_nodelay(true);
+			let _ = spectator.set_write_timeout(Some(WRITE_TIMEOUT));
+			let mut messages = welcome(game);
+			messages.extend(self.cursor.map(|(x, y)| CoopMessage::Cursor(PLAYER, x, y)));
+			if send(&mut spectator, &messages) {
+				self.spectators.push(spectator);
+			}
+		}
+	}
+}
+
+// The board's size and everything which has happened on it so far
+fn welcome(game: &Minesweeper) -> Vec<CoopMessage> {
+	let mut messages = vec![CoopMessage::Welcome {
+		player: PLAYER,
+		width: game.board.width(),
+		height: game.board.height(),
+		mines: game.board.mines(),
+	}];
+	messages.extend(coop::game_messages(game, |_, _| PLAYER));
+	messages
+}
+
+// Whether the spectator is still there to be sent messages
+fn send(spectator: &mut TcpStream, messages: &[CoopMessage]) -> bool {
+	messages.iter().all(|message| writeln!(spectator, "{}", message).is_ok())
+}
@@ -0,0 +1,80 @@
+use std::{
+	error::Error,
+	fmt,
+	io::{BufRead, BufReader, Write},
+	net::TcpStream,
+	sync::mpsc::{self, Receiver, TryRecvError},
+	thread,
+};
+
+use super::protocol::LineMessage;
+
+// A connection to a server, receiving messages of type `M`. Messages are read on a thread of their own,
+// so checking for them never blocks a frame.
+pub struct Connection<M> {
+	stream: TcpStream,
+	incoming: Receiver<M>,
+	open: bool,
+}
+
+impl<M: LineMessage + Send + 'static> Connection<M> {
+	pub fn connect(address: &str) -> Result<Self, Box<dyn Error>> {
+		let stream = TcpStream::connect(address)?;
+		stream.set_nodelay(true)?;
+		let reader = BufReader::new(stream.try_clone()?);
+		let (sender, incoming) = mpsc::channel();
+		thread::spawn(move || {
+			for line in reader.lines() {
+				let Ok(line) = line else {
+					break;
+				};
+				match M::parse(&line) {
+					Ok(message) => {
+						if sender.send(message).is_err() {
+							break;
+						}
+					}
+					Err(error) => eprintln!("Ignoring message from server: {}", error),
+				}
+			}
+		});
+		Ok(Self {
+			stream,
+			incoming,
+			open: true,
+		})
+	}
+
+	pub fn send(&mut self, message: &impl fmt::Display) {
+		if !self.open {
+			return;
+		}
+		if let Err(error) = writeln!(self.stream, "{}", message) {
+			eprintln!("Lost connection to server: {}", error);
+			self.open = false;
+		}
+	}
+
+	// The next message, if one has arrived
+	pub fn poll(&mut self) -> Option<M> {
+		match self.incoming.try_recv() {
+			Ok(message) => Some(message),
+			Err(TryRecvError::Empty) => None,
+			Err(TryRecvError::Disconnected) => {
+				self.open = false;
+				None
+			}
+		}
+	}
+
+	// Waits for the next message, or None once the server has closed the connection.
+	pub fn wait(&mut self) -> Option<M> {
+		let message = self.incoming.recv().ok();
+		self.open &= message.is_some();
+		message
+	}
+
+	pub fn is_open(&self) -> bool {
+		self.open
+	}
+}
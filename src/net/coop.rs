@@ -0,0 +1,374 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+use crate::logic::{
+	first_click::FirstClick,
+	game_board::GameBoard,
+	tile::{Tile, TileModifier},
+	visible::VisibleTile,
+	GameEvent, GameState, Minesweeper,
+};
+
+use super::{
+	connection::Connection,
+	protocol::{value, LineMessage, DEFAULT_ADDRESS},
+};
+
+// What a player asks the server to do. Only the server changes the board, so clicks are sent rather than played.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Intent {
+	Reveal(usize, usize),
+	Modify(usize, usize),
+	Question(usize, usize),
+	Chord(usize, usize),
+	// The tile the player is pointing at, shown to everyone else
+	Cursor(usize, usize),
+	Reset,
+}
+
+impl LineMessage for Intent {
+	fn parse(line: &str) -> Result<Self, Box<dyn Error>> {
+		let mut parts = line.split_whitespace();
+		let kind = parts.next().ok_or("Empty message")?;
+		let parts = &mut parts;
+		let intent = match kind {
+			"reveal" => Intent::Reveal(value(parts, line)?, value(parts, line)?),
+			"modify" => Intent::Modify(value(parts, line)?, value(parts, line)?),
+			"question" => Intent::Question(value(parts, line)?, value(parts, line)?),
+			"chord" => Intent::Chord(value(parts, line)?, value(parts, line)?),
+			"cursor" => Intent::Cursor(value(parts, line)?, value(parts, line)?),
+			"reset" => Intent::Reset,
+			_ => return Err(format!("Unknown message: {}", line).into()),
+		};
+		Ok(intent)
+	}
+}
+
+impl fmt::Display for Intent {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Intent::Reveal(x, y) => write!(f, "reveal {} {}", x, y),
+			Intent::Modify(x, y) => write!(f, "modify {} {}", x, y),
+			Intent::Question(x, y) => write!(f, "question {} {}", x, y),
+			Intent::Chord(x, y) => write!(f, "chord {} {}", x, y),
+			Intent::Cursor(x, y) => write!(f, "cursor {} {}", x, y),
+			Intent::Reset => write!(f, "reset"),
+		}
+	}
+}
+
+// What the server tells every player, in the order it happened on the shared board. Broadcast games are streamed
+// to their spectators the same way.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CoopMessage {
+	// Sent to a player when they join, with the id which marks their cursor and flags
+	Welcome {
+		player: usize,
+		width: usize,
+		height: usize,
+		mines: usize,
+	},
+	// A revealed tile and its number, or None for a mine
+	Reveal(usize, usize, Option<u8>),
+	// The flag's owner comes last
+	Flag(usize, usize, usize),
+	Question(usize, usize),
+	// A flag or question mark was taken away
+	Clear(usize, usize),
+	// Where a mine was, sent for every mine once the game is over
+	Mine(usize, usize),
+	Chord(usize, usize),
+	SweepDone,
+	Lose(usize, usize),
+	Win,
+	Reset,
+	// Seconds on the game's timer, sent to those who join late and when the game ends
+	Time(f64),
+	// Another player's id and the tile they are pointing at
+	Cursor(usize, usize, usize),
+	Left(usize),
+}
+
+impl LineMessage for CoopMessage {
+	fn parse(line: &str) -> Result<Self, Box<dyn Error>> {
+		let mut parts = line.split_whitespace();
+		let kind = parts.next().ok_or("Empty message")?;
+		let parts = &mut parts;
+		let message = match kind {
+			"welcome" => CoopMessage::Welcome {
+				player: value(parts, line)?,
+				width: value(parts, line)?,
+				height: value(parts, line)?,
+				mines: value(parts, line)?,
+			},
+			"reveal" => {
+				let (x, y) = (value(parts, line)?, value(parts, line)?);
+				match value::<String>(parts, line)?.as_str() {
+					"*" => CoopMessage::Reveal(x, y, None),
+					adjacent => CoopMessage::Reveal(x, y, Some(adjacent.parse()?)),
+				}
+			}
+			"flag" => CoopMessage::Flag(value(parts, line)?, value(parts, line)?, value(parts, line)?),
+			"question" => CoopMessage::Question(value(parts, line)?, value(parts, line)?),
+			"clear" => CoopMessage::Clear(value(parts, line)?, value(parts, line)?),
+			"mine" => CoopMessage::Mine(value(parts, line)?, value(parts, line)?),
+			"chord" => CoopMessage::Chord(value(parts, line)?, value(parts, line)?),
+			"sweep_done" => CoopMessage::SweepDone,
+			"lose" => CoopMessage::Lose(value(parts, line)?, value(parts, line)?),
+			"win" => CoopMessage::Win,
+			"reset" => CoopMessage::Reset,
+			"time" => CoopMessage::Time(value(parts, line)?),
+			"cursor" => CoopMessage::Cursor(value(parts, line)?, value(parts, line)?, value(parts, line)?),
+			"left" => CoopMessage::Left(value(parts, line)?),
+			_ => return Err(format!("Unknown message: {}", line).into()),
+		};
+		Ok(message)
+	}
+}
+
+impl fmt::Display for CoopMessage {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			CoopMessage::Welcome {
+				player,
+				width,
+				height,
+				mines,
+			} => write!(f, "welcome {} {} {} {}", player, width, height, mines),
+			CoopMessage::Reveal(x, y, Some(adjacent)) => write!(f, "reveal {} {} {}", x, y, adjacent),
+			CoopMessage::Reveal(x, y, None) => write!(f, "reveal {} {} *", x, y),
+			CoopMessage::Flag(x, y, player) => write!(f, "flag {} {} {}", x, y, player),
+			CoopMessage::Question(x, y) => write!(f, "question {} {}", x, y),
+			CoopMessage::Clear(x, y) => write!(f, "clear {} {}", x, y),
+			CoopMessage::Mine(x, y) => write!(f, "mine {} {}", x, y),
+			CoopMessage::Chord(x, y) => write!(f, "chord {} {}", x, y),
+			CoopMessage::SweepDone => write!(f, "sweep_done"),
+			CoopMessage::Lose(x, y) => write!(f, "lose {} {}", x, y),
+			CoopMessage::Win => write!(f, "win"),
+			CoopMessage::Reset => write!(f, "reset"),
+			CoopMessage::Time(time) => write!(f, "time {:.3}", time),
+			CoopMessage::Cursor(player, x, y) => write!(f, "cursor {} {} {}", player, x, y),
+			CoopMessage::Left(player) => write!(f, "left {}", player),
+		}
+	}
+}
+
+// What the players of a game need to hear of one of its events. Flags are marked as `player`'s.
+pub fn event_messages(event: &GameEvent, game: &Minesweeper, player: usize) -> Vec<CoopMessage> {
+	let board = &game.board;
+	let message = match *event {
+		GameEvent::RevealTile(x, y, tile) => CoopMessage::Reveal(x, y, adjacent(tile)),
+		// Set off mines stay on the board of a hot-seat game
+		GameEvent::Detonate(x, y, _) => CoopMessage::Reveal(x, y, None),
+		GameEvent::FlagTile(x, y, _) | GameEvent::QuestionTile(x, y, _) => {
+			return modifier_message(board, x, y, player).into_iter().collect();
+		}
+		GameEvent::Chord(x, y) => CoopMessage::Chord(x, y),
+		GameEvent::SweepDone => CoopMessage::SweepDone,
+		GameEvent::Lose(x, y, _) => CoopMessage::Lose(x, y),
+		GameEvent::Win => CoopMessage::Win,
+		GameEvent::Reset => CoopMessage::Reset,
+		GameEvent::GameEnd(_) => return mine_messages(board),
+		GameEvent::SweepBegin | GameEvent::InitDone => return vec![],
+	};
+	let mut messages = vec![message];
+	// Everyone's timer stops at the time the game ended
+	if matches!(message, CoopMessage::Lose(..) | CoopMessage::Win) {
+		messages.extend(game.get_time().map(CoopMessage::Time));
+	}
+	messages
+}
+
+// Everything needed to bring someone who joins late up to date with a game, with `flag_owner` giving who placed
+// each flag.
+pub fn game_messages(game: &Minesweeper, flag_owner: impl Fn(usize, usize) -> usize) -> Vec<CoopMessage> {
+	let board = &game.board;
+	let mut messages = vec![];
+	if game.state == GameState::Empty {
+		return messages;
+	}
+	let mut mine_hit = None;
+	for y in 0..board.height() {
+		for x in 0..board.width() {
+			let Some(tile) = board.get_tile(x, y) else {
+				continue;
+			};
+			if tile.swept {
+				if tile.visible() == VisibleTile::Exploded {
+					mine_hit = mine_hit.or(Some((x, y)));
+				}
+				messages.push(CoopMessage::Reveal(x, y, adjacent(*tile)));
+			}
+			if tile.modifier.is_some() {
+				messages.extend(modifier_message(board, x, y, flag_owner(x, y)));
+			}
+		}
+	}
+	match (&game.state, mine_hit) {
+		(GameState::GameOver, Some((x, y))) => messages.push(CoopMessage::Lose(x, y)),
+		(GameState::Victory, _) => messages.push(CoopMessage::Win),
+		_ => (),
+	}
+	if matches!(game.state, GameState::GameOver | GameState::Victory) {
+		messages.extend(mine_messages(board));
+	}
+	messages.extend(game.get_time().map(CoopMessage::Time));
+	messages
+}
+
+// The number on a revealed tile, or None for a mine
+fn adjacent(tile: Tile) -> Option<u8> {
+	match tile.visible() {
+		VisibleTile::Revealed(adjacent) => Some(adjacent),
+		_ => None,
+	}
+}
+
+// The flag or question mark on a tile, or that it has neither
+fn modifier_message(board: &GameBoard, x: usize, y: usize, player: usize) -> Option<CoopMessage> {
+	match board.get_tile(x, y)?.modifier {
+		Some(TileModifier::Flagged) => Some(CoopMessage::Flag(x, y, player)),
+		Some(TileModifier::Unsure) => Some(CoopMessage::Question(x, y)),
+		None => Some(CoopMessage::Clear(x, y)),
+	}
+}
+
+fn mine_messages(board: &GameBoard) -> Vec<CoopMessage> {
+	(0..board.height())
+		.flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+		.filter(|&(x, y)| board.shown_mine(x, y) == Some(true))
+		.map(|(x, y)| CoopMessage::Mine(x, y))
+		.collect()
+}
+
+// A player's view of a board shared with others. The game here only mirrors the server's: every click is sent
+// as an intent, and the board changes when the server says it did. Spectators see a broadcast game the same way,
+// but can't send anything.
+pub struct CoopClient {
+	connection: Connection<CoopMessage>,
+	pub read_only: bool,
+	// This player's id, once the server has sent it
+	pub player: Option<usize>,
+	// Where the other players are pointing
+	pub cursors: HashMap<usize, (usize, usize)>,
+	pub flag_owners: HashMap<(usize, usize), usize>,
+	sent_cursor: Option<(usize, usize)>,
+}
+
+impl CoopClient {
+	pub fn join(address: &str) -> Result<Self, Box<dyn Error>> {
+		let address = if address.is_empty() { DEFAULT_ADDRESS } else { address };
+		Ok(Self {
+			connection: Connection::connect(address)?,
+			read_only: false,
+			player: None,
+			cursors: HashMap::new(),
+			flag_owners: HashMap::new(),
+			sent_cursor: None,
+		})
+	}
+
+	// Watches a game broadcast from this address. Empty means the default address.
+	pub fn spectate(address: &str) -> Result<Self, Box<dyn Error>> {
+		Ok(Self {
+			read_only: true,
+			..Self::join(address)?
+		})
+	}
+
+	pub fn send(&mut self, intent: Intent) {
+		if !self.read_only {
+			self.connection.send(&intent);
+		}
+	}
+
+	// Tells the others where this player is pointing, when it has changed
+	pub fn send_cursor(&mut self, x: usize, y: usize) {
+		if self.sent_cursor != Some((x, y)) {
+			self.sent_cursor = Some((x, y));
+			self.send(Intent::Cursor(x, y));
+		}
+	}
+
+	pub fn is_open(&self) -> bool {
+		self.connection.is_open()
+	}
+
+	// Applies every message which has arrived to the mirrored game. Returns true when the server has just
+	// welcomed this player, which replaces the game's board with the shared one.
+	pub fn receive(&mut self, game: &mut Minesweeper) -> bool {
+		let mut welcomed = false;
+		while let Some(message) = self.connection.poll() {
+			welcomed |= self.handle(message, game);
+		}
+		welcomed
+	}
+
+	fn handle(&mut self, message: CoopMessage, game: &mut Minesweeper) -> bool {
+		let current = |game: &Minesweeper, x, y| game.board.get_tile(x, y).copied().unwrap_or_default();
+		match message {
+			CoopMessage::Welcome {
+				player,
+				width,
+				height,
+				mines,
+			} => {
+				// The server places the mines, by its own rule
+				game.board.first_click = FirstClick::default();
+				if let Err(error) = game.board.update(width, height, mines) {
+					eprintln!("Could not join the game: {}", error);
+					return false;
+				}
+				game.set_hot_seat(None);
+				self.player = Some(player);
+				self.cursors.clear();
+				self.flag_owners.clear();
+				return true;
+			}
+			CoopMessage::Reveal(x, y, adjacent) => {
+				let visible = adjacent.map_or(VisibleTile::Exploded, VisibleTile::Revealed);
+				if let Some(tile) = game.board.mirror_tile(x, y, visible) {
+					game.mirror(GameEvent::RevealTile(x, y, tile));
+				}
+			}
+			CoopMessage::Flag(x, y, _) | CoopMessage::Question(x, y) | CoopMessage::Clear(x, y) => {
+				let before = current(game, x, y);
+				let visible = match message {
+					CoopMessage::Flag(_, _, player) => {
+						self.flag_owners.insert((x, y), player);
+						VisibleTile::Flagged
+					}
+					CoopMessage::Question(_, _) => VisibleTile::Questioned,
+					_ => VisibleTile::Hidden,
+				};
+				if visible != VisibleTile::Flagged {
+					self.flag_owners.remove(&(x, y));
+				}
+				game.board.mirror_tile(x, y, visible);
+				// Like the game's own events, these carry the tile as it was before the change, and any change to a flag
+				// is a flag event
+				if visible == VisibleTile::Flagged || before.modifier == Some(TileModifier::Flagged) {
+					game.mirror(GameEvent::FlagTile(x, y, before));
+				} else {
+					game.mirror(GameEvent::QuestionTile(x, y, before));
+				}
+			}
+			CoopMessage::Mine(x, y) => game.board.mirror_mine(x, y),
+			CoopMessage::Chord(x, y) => game.mirror(GameEvent::Chord(x, y)),
+			CoopMessage::SweepDone => game.mirror(GameEvent::SweepDone),
+			CoopMessage::Lose(x, y) => game.mirror(GameEvent::Lose(x, y, current(game, x, y))),
+			CoopMessage::Win => game.mirror(GameEvent::Win),
+			CoopMessage::Time(time) => game.mirror_time(time),
+			CoopMessage::Reset => {
+				self.flag_owners.clear();
+				game.mirror(GameEvent::Reset);
+			}
+			CoopMessage::Cursor(player, x, y) => {
+				self.cursors.insert(player, (x, y));
+			}
+			CoopMessage::Left(player) => {
+				self.cursors.remove(&player);
+			}
+		}
+		false
+	}
+}
@@ -0,0 +1,207 @@
+use std::{
+	collections::HashMap,
+	error::Error,
+	io::{BufRead, BufReader, Write},
+	net::{Shutdown, TcpListener, TcpStream},
+	sync::mpsc::{self, Sender},
+	thread,
+	time::Duration,
+};
+
+use crate::{
+	logic::{first_click::FirstClick, game_board::ModifyMode, tile::TileModifier, GameEvent, Minesweeper},
+	util::time_seed,
+};
+
+use super::{
+	coop::{self, CoopMessage, Intent},
+	protocol::{LineMessage, DEFAULT_ADDRESS},
+};
+
+// Players who can't take messages this quickly are dropped rather than holding up everyone else
+const WRITE_TIMEOUT: Duration = Duration::from_millis(500);
+
+// Everything the server reacts to arrives through one channel, so the game only changes on its own thread
+enum Input {
+	Joined(usize, TcpStream),
+	Intent(usize, Intent),
+	Left(usize),
+}
+
+// Holds the one game every player shares and tells them all what happens to it
+struct Server {
+	game: Minesweeper,
+	players: HashMap<usize, TcpStream>,
+	cursors: HashMap<usize, (usize, usize)>,
+	flag_owners: HashMap<(usize, usize), usize>,
+}
+
+// Runs the authoritative game of a shared board until the process is stopped.
+pub fn run_server(address: &str, board: (usize, usize, usize)) -> Result<(), Box<dyn Error>> {
+	let address = if address.is_empty() { DEFAULT_ADDRESS } else { address };
+	serve(TcpListener::bind(address)?, board)
+}
+
+// Shares a board of this width, height and mine count with everyone connecting to the listener.
+pub fn serve(listener: TcpListener, board: (usize, usize, usize)) -> Result<(), Box<dyn Error>> {
+	let (width, height, mines) = board;
+	let mut server = Server {
+		game: Minesweeper::new(width, height, mines, FirstClick::default())?,
+		players: HashMap::new(),
+		cursors: HashMap::new(),
+		flag_owners: HashMap::new(),
+	};
+	// Everyone has both flags and question marks, each on a key of their own
+	server.game.board.modify_mode = ModifyMode::Separate;
+	server.new_game();
+	// No one is there to hear about the first game starting
+	server.game.events.clear();
+	println!(
+		"Co-op server listening on {} with a {}x{} board and {} mines",
+		listener.local_addr()?,
+		width,
+		height,
+		mines
+	);
+	let (sender, inputs) = mpsc::channel();
+	thread::spawn(move || accept(listener, sender));
+	for input in inputs {
+		match input {
+			Input::Joined(player, stream) => server.join(player, stream),
+			Input::Intent(player, intent) => server.apply(player, intent),
+			Input::Left(player) => server.leave(player),
+		}
+	}
+	Ok(())
+}
+
+// Gives every connection an id and a thread reading its intents.
+fn accept(listener: TcpListener, sender: Sender<Input>) {
+	for (player, stream) in listener.incoming().enumerate() {
+		let stream = match stream.and_then(|stream| Ok((stream.try_clone()?, stream))) {
+			Ok(streams) => streams,
+			Err(error) => {
+				eprintln!("Could not accept connection: {}", error);
+				continue;
+			}
+		};
+		let (reader, writer) = stream;
+		if sender.send(Input::Joined(player, writer)).is_err() {
+			return;
+		}
+		let sender = sender.clone();
+		thread::spawn(move || {
+			for line in BufReader::new(reader).lines() {
+				let Ok(line) = line else {
+					break;
+				};
+				match Intent::parse(&line) {
+					Ok(intent) => {
+						if sender.send(Input::Intent(player, intent)).is_err() {
+							return;
+						}
+					}
+					Err(error) => eprintln!("Ignoring message from player {}: {}", player, error),
+				}
+			}
+			let _ = sender.send(Input::Left(player));
+		});
+	}
+}
+
+impl Server {
+	fn new_game(&mut self) {
+		self.game.board.seed = Some(time_seed());
+		self.game.reset();
+	}
+
+	// Welcomes a player and brings them up to date with the board as it is.
+	fn join(&mut self, player: usize, mut stream: TcpStream) {
+		let _ = stream.set_nodelay(true);
+		let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+		let board = &self.game.board;
+		let mut messages = vec![CoopMessage::Welcome {
+			player,
+			width: board.width(),
+			height: board.height(),
+			mines: board.mines(),
+		}];
+		messages.extend(coop::game_messages(&self.game, |x, y| {
+			self.flag_owners.get(&(x, y)).copied().unwrap_or_default()
+		}));
+		messages.extend(self.cursors.iter().map(|(&other, &(x, y))| CoopMessage::Cursor(other, x, y)));
+		for message in messages {
+			if writeln!(stream, "{}", message).is_err() {
+				return;
+			}
+		}
+		println!("Player {} joined", player);
+		self.players.insert(player, stream);
+	}
+
+	fn apply(&mut self, player: usize, intent: Intent) {
+		match intent {
+			Intent::Reveal(x, y) => self.game.reveal(x, y),
+			Intent::Modify(x, y) => self.game.modify(x, y),
+			Intent::Question(x, y) => self.game.question(x, y),
+			Intent::Chord(x, y) => self.game.chord(x, y),
+			Intent::Cursor(x, y) => {
+				self.cursors.insert(player, (x, y));
+				self.broadcast(CoopMessage::Cursor(player, x, y), Some(player));
+			}
+			Intent::Reset => self.new_game(),
+		}
+		while let Some(event) = self.game.events.pop() {
+			self.track_flags(&event, player);
+			for message in coop::event_messages(&event, &self.game, player) {
+				self.broadcast(message, None);
+			}
+		}
+	}
+
+	// Keeps track of who placed each flag, with `player` being the one whose intent caused the event
+	fn track_flags(&mut self, event: &GameEvent, player: usize) {
+		match *event {
+			GameEvent::FlagTile(x, y, _) | GameEvent::QuestionTile(x, y, _) => {
+				if self.game.board.get_tile(x, y).and_then(|tile| tile.modifier) == Some(TileModifier::Flagged) {
+					self.flag_owners.insert((x, y), player);
+				} else {
+					self.flag_owners.remove(&(x, y));
+				}
+			}
+			// Floods take markers off the tiles they reveal without a flag event for each
+			GameEvent::RevealTile(x, y, _) => {
+				self.flag_owners.remove(&(x, y));
+			}
+			GameEvent::Reset => self.flag_owners.clear(),
+			_ => (),
+		}
+	}
+
+	// Tells everyone else a player has gone, whether they disconnected or stopped taking messages
+	fn leave(&mut self, player: usize) {
+		// Their reading thread also reports them leaving once their connection is shut
+		let Some(stream) = self.players.remove(&player) else {
+			return;
+		};
+		let _ = stream.shutdown(Shutdown::Both);
+		self.cursors.remove(&player);
+		println!("Player {} left", player);
+		self.broadcast(CoopMessage::Left(player), None);
+	}
+
+	// Sends a message to every player, except the one it is about when given. Players it can't reach are dropped.
+	fn broadcast(&mut self, message: CoopMessage, except: Option<usize>) {
+		let unreachable: Vec<usize> = self
+			.players
+			.iter_mut()
+			.filter_map(|(&player, stream)| {
+				(Some(player) != except && writeln!(stream, "{}", message).is_err()).then_some(player)
+			})
+			.collect();
+		for player in unreachable {
+			eprintln!("Could not reach player {}", player);
+			self.leave(player);
+		}
+	}
+}
@@ -0,0 +1,100 @@
+use std::{error::Error, fmt, str::FromStr, str::SplitWhitespace};
+
+use crate::logic::first_click::FirstClick;
+
+// Where the relay listens, and clients connect, unless told otherwise
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+
+// Anything sent over a connection, as one line of text. `Display` writes the line and `parse` reads it back.
+pub trait LineMessage: fmt::Display + Sized {
+	fn parse(line: &str) -> Result<Self, Box<dyn Error>>;
+}
+
+// Messages of the race protocol, sent as one line of text each. The relay sends `Start` to both players of a
+// race, then passes everything one player sends on to the other.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+	// The board both players race on: its seed, size and mine count, the tile both start by revealing, and the first
+	// click rule which keeps that tile safe
+	Start {
+		seed: u64,
+		width: usize,
+		height: usize,
+		mines: usize,
+		start: (usize, usize),
+		first_click: FirstClick,
+	},
+	Reveal(usize, usize),
+	Flag(usize, usize),
+	Unflag(usize, usize),
+	Lose(usize, usize),
+	// Seconds taken to clear the board
+	Win(f64),
+	// Sent by the relay when the opponent disconnects
+	Left,
+}
+
+impl LineMessage for Message {
+	fn parse(line: &str) -> Result<Self, Box<dyn Error>> {
+		let mut parts = line.split_whitespace();
+		let kind = parts.next().ok_or("Empty message")?;
+		let parts = &mut parts;
+		let message = match kind {
+			"start" => Message::Start {
+				seed: value(parts, line)?,
+				width: value(parts, line)?,
+				height: value(parts, line)?,
+				mines: value(parts, line)?,
+				start: (value(parts, line)?, value(parts, line)?),
+				first_click: parts.next().ok_or(format!("Message is missing a value: {}", line))?.parse()?,
+			},
+			"reveal" => Message::Reveal(value(parts, line)?, value(parts, line)?),
+			"flag" => Message::Flag(value(parts, line)?, value(parts, line)?),
+			"unflag" => Message::Unflag(value(parts, line)?, value(parts, line)?),
+			"lose" => Message::Lose(value(parts, line)?, value(parts, line)?),
+			"win" => Message::Win(value(parts, line)?),
+			"left" => Message::Left,
+			_ => return Err(format!("Unknown message: {}", line).into()),
+		};
+		Ok(message)
+	}
+}
+
+// The next value of a message
+pub fn value<T: FromStr>(parts: &mut SplitWhitespace, line: &str) -> Result<T, Box<dyn Error>>
+where
+	T::Err: Error + 'static,
+{
+	Ok(parts.next().ok_or(format!("Message is missing a value: {}", line))?.parse()?)
+}
+
+impl fmt::Display for Message {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Message::Start {
+				seed,
+				width,
+				height,
+				mines,
+				start,
+				first_click,
+			} => write!(
+				f,
+				"start {} {} {} {} {} {} {}",
+				seed,
+				width,
+				height,
+				mines,
+				start.0,
+				start.1,
+				first_click.name()
+			),
+			Message::Reveal(x, y) => write!(f, "reveal {} {}", x, y),
+			Message::Flag(x, y) => write!(f, "flag {} {}", x, y),
+			Message::Unflag(x, y) => write!(f, "unflag {} {}", x, y),
+			Message::Lose(x, y) => write!(f, "lose {} {}", x, y),
+			Message::Win(time) => write!(f, "win {:.3}", time),
+			Message::Left => write!(f, "left"),
+		}
+	}
+}
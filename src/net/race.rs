@@ -0,0 +1,206 @@
+use std::{collections::HashSet, error::Error, thread, time::Duration};
+
+use macroquad::miniquad::date;
+
+use crate::{
+	agent::{simple::SimpleAgent, Agent},
+	logic::{tile::TileModifier, visible::VisibleBoard, GameEvent, GameState, Minesweeper},
+	util::Subscriber,
+};
+
+use super::{
+	connection::Connection,
+	protocol::{Message, DEFAULT_ADDRESS},
+};
+
+// Pause between the headless bot's moves, so both players' progress interleaves
+const BOT_MOVE_DELAY: Duration = Duration::from_millis(10);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Outcome {
+	Lost,
+	// Seconds taken to clear the board
+	Won(f64),
+	Left,
+}
+
+// What is known of the other player's game, from the progress they stream
+#[derive(Default)]
+pub struct Opponent {
+	pub revealed: HashSet<(usize, usize)>,
+	pub flags: HashSet<(usize, usize)>,
+	pub outcome: Option<Outcome>,
+}
+
+// Two players clearing the same board, each seeing how far the other has got. Both games are generated from
+// the seed the relay hands out and start by revealing the same tile, so they have the same mines.
+pub struct Race {
+	connection: Connection<Message>,
+	pub opponent: Opponent,
+	// Safe tiles on the board, for measuring progress. Zero until the race starts.
+	pub non_mine_tiles: usize,
+	// When the race started, in seconds on miniquad's clock
+	start_time: Option<f64>,
+}
+
+impl Race {
+	pub fn join(address: &str) -> Result<Self, Box<dyn Error>> {
+		let address = if address.is_empty() { DEFAULT_ADDRESS } else { address };
+		Ok(Self {
+			connection: Connection::connect(address)?,
+			opponent: Opponent::default(),
+			non_mine_tiles: 0,
+			start_time: None,
+		})
+	}
+
+	pub fn has_started(&self) -> bool {
+		self.start_time.is_some()
+	}
+
+	// Both players are done, or there is no one left to race
+	pub fn is_over(&self, game: &Minesweeper) -> bool {
+		let finished = matches!(game.state, GameState::GameOver | GameState::Victory);
+		(finished && self.opponent.outcome.is_some()) || self.opponent.outcome == Some(Outcome::Left)
+	}
+
+	// Handles every message which has arrived. Returns true when the race has just started, which replaces
+	// the game's board with the race's.
+	pub fn receive(&mut self, game: &mut Minesweeper) -> bool {
+		let mut started = false;
+		while let Some(message) = self.connection.poll() {
+			started |= self.handle(message, game);
+		}
+		if !self.connection.is_open() && self.opponent.outcome.is_none() {
+			self.opponent.outcome = Some(Outcome::Left);
+		}
+		started
+	}
+
+	fn handle(&mut self, message: Message, game: &mut Minesweeper) -> bool {
+		match message {
+			Message::Start {
+				seed,
+				width,
+				height,
+				mines,
+				start,
+				first_click,
+			} => {
+				// Both players have to generate the same board
+				game.board.first_click = first_click;
+				if let Err(error) = game.board.update(width, height, mines) {
+					eprintln!("Could not start the race: {}", error);
+					return false;
+				}
+				game.board.seed = Some(seed);
+				game.set_hot_seat(None);
+				game.reveal(start.0, start.1);
+				self.opponent = Opponent::default();
+				self.non_mine_tiles = width * height - mines;
+				self.start_time = Some(date::now());
+				return true;
+			}
+			Message::Reveal(x, y) => {
+				self.opponent.revealed.insert((x, y));
+			}
+			Message::Flag(x, y) => {
+				self.opponent.flags.insert((x, y));
+			}
+			Message::Unflag(x, y) => {
+				self.opponent.flags.remove(&(x, y));
+			}
+			Message::Lose(_, _) => self.opponent.outcome = Some(Outcome::Lost),
+			Message::Win(time) => self.opponent.outcome = Some(Outcome::Won(time)),
+			// Players who have finished leave too, which doesn't change how they did
+			Message::Left => self.opponent.outcome = self.opponent.outcome.or(Some(Outcome::Left)),
+		}
+		false
+	}
+
+	// Share of the safe tiles which have been revealed, from 0 to 1
+	pub fn progress(&self, revealed: usize) -> f32 {
+		if self.non_mine_tiles == 0 {
+			return 0.0;
+		}
+		revealed as f32 / self.non_mine_tiles as f32
+	}
+
+	pub fn summary(&self, game: &Minesweeper) -> String {
+		let you = match game.state {
+			GameState::Victory => format!("You cleared the board in {:.3} seconds", game.get_time().unwrap_or_default()),
+			GameState::GameOver => String::from("You hit a mine"),
+			_ => String::from("You didn't finish"),
+		};
+		let opponent = match self.opponent.outcome {
+			Some(Outcome::Won(time)) => format!("your opponent cleared it in {:.3} seconds", time),
+			Some(Outcome::Lost) => String::from("your opponent hit a mine"),
+			Some(Outcome::Left) | None => String::from("your opponent left"),
+		};
+		format!(
+			"{}, {} ({} of {} safe tiles revealed against {})",
+			you,
+			opponent,
+			game.board.revealed_tiles,
+			self.non_mine_tiles,
+			self.opponent.revealed.len()
+		)
+	}
+}
+
+// Streams this player's progress to the opponent
+impl Subscriber<GameEvent> for Race {
+	fn notify(&mut self, event: &GameEvent) {
+		if !self.has_started() {
+			return;
+		}
+		let message = match event {
+			GameEvent::RevealTile(x, y, _) => Message::Reveal(*x, *y),
+			// The tile is as it was before the change, so an unflagged tile is getting a flag
+			GameEvent::FlagTile(x, y, tile) if tile.modifier != Some(TileModifier::Flagged) => Message::Flag(*x, *y),
+			GameEvent::FlagTile(x, y, _) | GameEvent::QuestionTile(x, y, _) => Message::Unflag(*x, *y),
+			GameEvent::Lose(x, y, _) => Message::Lose(*x, *y),
+			GameEvent::Win => Message::Win(self.start_time.map_or(0.0, |start| date::now() - start)),
+			_ => return,
+		};
+		self.connection.send(&message);
+	}
+}
+
+// Plays a race without a window, with the simple agent making the moves.
+pub fn run_headless(address: &str) -> Result<(), Box<dyn Error>> {
+	let (race, game) = play_headless(address)?;
+	println!("{}", race.summary(&game));
+	Ok(())
+}
+
+// Plays a race with the simple agent until both players are done, giving back the race and the game as they ended
+pub fn play_headless(address: &str) -> Result<(Race, Minesweeper), Box<dyn Error>> {
+	let mut race = Race::join(address)?;
+	let mut game = Minesweeper::default();
+	println!("Waiting for an opponent");
+	while !race.has_started() {
+		let message = race.connection.wait().ok_or("The relay closed the connection")?;
+		race.handle(message, &mut game);
+	}
+	println!("Race started on a {}x{} board", game.board.width(), game.board.height());
+
+	let mut agent = SimpleAgent::new(game.board.seed.unwrap_or_default() ^ std::process::id() as u64);
+	while game.state == GameState::Playing {
+		agent.choose_move(&VisibleBoard::from(&game.board)).play(&mut game);
+		game.events.dispatch(&mut [&mut race]);
+		race.receive(&mut game);
+		thread::sleep(BOT_MOVE_DELAY);
+	}
+	// The start reveal may have been the whole game
+	game.events.dispatch(&mut [&mut race]);
+	while !race.is_over(&game) {
+		match race.connection.wait() {
+			Some(message) => {
+				race.handle(message, &mut game);
+			}
+			None => race.opponent.outcome = race.opponent.outcome.or(Some(Outcome::Left)),
+		}
+	}
+	Ok((race, game))
+}
@@ -0,0 +1,109 @@
+use std::{
+	error::Error,
+	io::{BufRead, BufReader, ErrorKind, Write},
+	net::{Shutdown, TcpListener, TcpStream},
+	thread,
+};
+
+use crate::{
+	logic::{first_click::FirstClick, game_board::GameBoard},
+	util::time_seed,
+};
+
+use super::protocol::{LineMessage, Message};
+
+// Races start by revealing the middle tile, so the board needs a first click rule which keeps it safe and room for
+// the mines besides what that rule keeps clear
+pub fn check_board(board: (usize, usize, usize), first_click: FirstClick) -> Result<(), Box<dyn Error>> {
+	if first_click == FirstClick::Unprotected {
+		return Err("Races start by revealing the middle tile, which the 'none' first click rule doesn't keep safe".into());
+	}
+	let (width, height, mines) = board;
+	GameBoard::new(width, height, mines, first_click)?;
+	Ok(())
+}
+
+// Pairs up the players connecting to the listener as they arrive, until the process is stopped. Each pair gets
+// the same seeded board, then everything one player sends is passed on to the other.
+pub fn serve(listener: TcpListener, board: (usize, usize, usize), first_click: FirstClick) {
+	let mut waiting: Option<TcpStream> = None;
+	for stream in listener.incoming() {
+		let stream = match stream {
+			Ok(stream) => stream,
+			Err(error) => {
+				eprintln!("Could not accept connection: {}", error);
+				continue;
+			}
+		};
+		// A player who left while waiting is replaced rather than raced
+		match waiting.take() {
+			Some(first) if is_connected(&first) => {
+				if let Err(error) = start_race(first, stream, board, first_click) {
+					eprintln!("Could not start race: {}", error);
+				}
+			}
+			_ => waiting = Some(stream),
+		}
+	}
+}
+
+// Whether a player waiting for an opponent is still there. They send nothing while waiting, so a read which would
+// block means the connection is open, and the end of the stream or an error means it isn't.
+fn is_connected(stream: &TcpStream) -> bool {
+	if stream.set_nonblocking(true).is_err() {
+		return false;
+	}
+	let connected = match stream.peek(&mut [0]) {
+		Ok(read) => read > 0,
+		Err(error) => error.kind() == ErrorKind::WouldBlock,
+	};
+	stream.set_nonblocking(false).is_ok() && connected
+}
+
+fn start_race(
+	mut first: TcpStream,
+	mut second: TcpStream,
+	board: (usize, usize, usize),
+	first_click: FirstClick,
+) -> Result<(), Box<dyn Error>> {
+	let (width, height, mines) = board;
+	let start = Message::Start {
+		seed: time_seed(),
+		width,
+		height,
+		mines,
+		start: (width / 2, height / 2),
+		first_click,
+	};
+	for player in [&mut first, &mut second] {
+		player.set_nodelay(true)?;
+		writeln!(player, "{}", start)?;
+	}
+	println!("Race started between {} and {}", first.peer_addr()?, second.peer_addr()?);
+	let (first_copy, second_copy) = (first.try_clone()?, second.try_clone()?);
+	thread::spawn(move || forward(first, second_copy));
+	thread::spawn(move || forward(second, first_copy));
+	Ok(())
+}
+
+// Passes one player's progress on to the other, until the player disconnects.
+fn forward(from: TcpStream, mut to: TcpStream) {
+	for line in BufReader::new(&from).lines() {
+		let Ok(line) = line else {
+			break;
+		};
+		// Only progress is passed on, the relay alone starts races
+		match Message::parse(&line) {
+			Ok(Message::Start { .. } | Message::Left) => continue,
+			Ok(message) => {
+				if writeln!(to, "{}", message).is_err() {
+					break;
+				}
+			}
+			Err(error) => eprintln!("Ignoring message: {}", error),
+		}
+	}
+	let _ = writeln!(to, "{}", Message::Left);
+	let _ = to.shutdown(Shutdown::Both);
+	let _ = from.shutdown(Shutdown::Both);
+}
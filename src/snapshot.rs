@@ -0,0 +1,47 @@
+use std::{error::Error, path::Path};
+
+use image::{imageops, ImageFormat, RgbaImage};
+
+use crate::{
+	gui::texture_store::TILE_LAYOUT,
+	logic::game_board::GameBoard,
+	sprite_loader::{load_sprite_images, sheet_layout},
+};
+
+// Draws the board the same way the game does, but into an image instead of a window.
+// With `reveal_all` the board is drawn as it appears once the game is over.
+// `tile_sheet` is a PNG laid out like the built in tile sheets, at any tile size.
+pub fn render_board_image(board: &GameBoard, reveal_all: bool, tile_sheet: &[u8]) -> Result<RgbaImage, Box<dyn Error>> {
+	let layout = sheet_layout(tile_sheet, TILE_LAYOUT.rows, TILE_LAYOUT.columns)?;
+	let sprites = load_sprite_images(tile_sheet, layout)?;
+	let (tile_width, tile_height) = layout.tile_size;
+
+	// Snapshots are taken by the player, who may look at the mines of a game still being played
+	let layout = reveal_all.then(|| board.layout());
+	let mut image = RgbaImage::new(board.width() as u32 * tile_width, board.height() as u32 * tile_height);
+	for x in 0..board.width() {
+		for y in 0..board.height() {
+			if let Some(tile) = board.get_tile(x, y) {
+				let mine = layout.as_ref().map(|layout| layout.is_mine(x, y));
+				let sprite = &sprites[tile.render(mine) as usize];
+				imageops::replace(
+					&mut image,
+					sprite,
+					x as i64 * tile_width as i64,
+					y as i64 * tile_height as i64,
+				);
+			}
+		}
+	}
+	Ok(image)
+}
+
+pub fn save_board_png(
+	board: &GameBoard,
+	reveal_all: bool,
+	tile_sheet: &[u8],
+	path: impl AsRef<Path>,
+) -> Result<(), Box<dyn Error>> {
+	render_board_image(board, reveal_all, tile_sheet)?.save_with_format(path, ImageFormat::Png)?;
+	Ok(())
+}
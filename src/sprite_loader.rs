@@ -1,25 +1,113 @@
-use std::error::Error;
+use std::{error::Error, io::Cursor};
 
-use image::{load_from_memory, EncodableLayout};
-use macroquad::texture::{FilterMode, Texture2D};
-pub fn load_sprites(bytes: &[u8], tile_size: (u32, u32), rows: usize, columns: usize) -> Result<Vec<Texture2D>, Box<dyn Error>> {
-	let sprite_sheet = load_from_memory(bytes)?.to_rgba8();
+use image::{io::Reader, load_from_memory, EncodableLayout, RgbaImage};
+use macroquad::{
+	math::Rect,
+	texture::{FilterMode, Texture2D},
+};
 
-	let mut sprite_list: Vec<Texture2D> = vec![];
-	let (tile_width, tile_height) = tile_size;
+// How the sprites of a sheet are arranged: a grid of equally sized sprites, read row by row.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SheetLayout {
+	pub tile_size: (u32, u32),
+	pub rows: usize,
+	pub columns: usize,
+}
 
-	for i in 0..(rows * columns) {
-		let x = (i % columns) as u32;
-		let y = (i / columns) as u32;
-		let tile = image::imageops::crop_imm(&sprite_sheet, x * tile_width, y * tile_height, tile_width, tile_height).to_image();
+impl SheetLayout {
+	pub fn sprite_count(&self) -> usize {
+		self.rows * self.columns
+	}
+}
+
+// Works out the sprite size of a sheet from its dimensions, for sheets which come in several resolutions.
+pub fn sheet_layout(bytes: &[u8], rows: usize, columns: usize) -> Result<SheetLayout, Box<dyn Error>> {
+	let (sheet_width, sheet_height) = Reader::new(Cursor::new(bytes)).with_guessed_format()?.into_dimensions()?;
+	if sheet_width % columns as u32 != 0 || sheet_height % rows as u32 != 0 {
+		return Err(format!(
+			"Sheet is {}x{}px, which can't be split into {} columns and {} rows",
+			sheet_width, sheet_height, columns, rows
+		)
+		.into());
+	}
+	Ok(SheetLayout {
+		tile_size: (sheet_width / columns as u32, sheet_height / rows as u32),
+		rows,
+		columns,
+	})
+}
+
+// A whole sheet uploaded as one texture. Drawing many sprites from the same texture lets macroquad
+// batch them into a few draw calls, where separate textures need one draw call each.
+#[derive(Copy, Clone, Debug)]
+pub struct SpriteAtlas {
+	pub texture: Texture2D,
+	pub layout: SheetLayout,
+}
+
+impl SpriteAtlas {
+	pub fn load(bytes: &[u8], layout: SheetLayout) -> Result<Self, Box<dyn Error>> {
+		let sheet = load_from_memory(bytes)?.to_rgba8();
+		check_sheet_size(sheet.dimensions(), layout)?;
+		let texture = Texture2D::from_rgba8(sheet.width() as u16, sheet.height() as u16, sheet.as_bytes());
+		texture.set_filter(FilterMode::Nearest);
+		Ok(Self { texture, layout })
+	}
+
+	// The part of the texture holding sprite `index`, to use as a `DrawTextureParams` source
+	pub fn source(&self, index: usize) -> Rect {
+		let (tile_width, tile_height) = self.layout.tile_size;
+		let x = (index % self.layout.columns) as u32 * tile_width;
+		let y = (index / self.layout.columns) as u32 * tile_height;
+		Rect::new(x as f32, y as f32, tile_width as f32, tile_height as f32)
+	}
+}
 
-		tile.as_bytes();
-		let tile_width = tile_width as u16;
-		let tile_height = tile_height as u16;
-		let tex = Texture2D::from_rgba8(tile_width, tile_height, &tile);
+pub fn load_sprites(bytes: &[u8], layout: SheetLayout) -> Result<Vec<Texture2D>, Box<dyn Error>> {
+	let mut sprite_list: Vec<Texture2D> = vec![];
+
+	for tile in load_sprite_images(bytes, layout)? {
+		let tile_width = tile.width() as u16;
+		let tile_height = tile.height() as u16;
+		let tex = Texture2D::from_rgba8(tile_width, tile_height, tile.as_bytes());
 		tex.set_filter(FilterMode::Nearest);
 
 		sprite_list.push(tex);
 	}
 	Ok(sprite_list)
 }
+
+// Same as `load_sprites`, but keeps the sprites as images so they can be used without a window.
+pub fn load_sprite_images(bytes: &[u8], layout: SheetLayout) -> Result<Vec<RgbaImage>, Box<dyn Error>> {
+	let sprite_sheet = load_from_memory(bytes)?.to_rgba8();
+	check_sheet_size(sprite_sheet.dimensions(), layout)?;
+	let (tile_width, tile_height) = layout.tile_size;
+
+	let mut sprite_list: Vec<RgbaImage> = vec![];
+
+	for i in 0..layout.sprite_count() {
+		let x = (i % layout.columns) as u32;
+		let y = (i / layout.columns) as u32;
+		let tile = image::imageops::crop_imm(&sprite_sheet, x * tile_width, y * tile_height, tile_width, tile_height).to_image();
+
+		sprite_list.push(tile);
+	}
+	Ok(sprite_list)
+}
+
+fn check_sheet_size((sheet_width, sheet_height): (u32, u32), layout: SheetLayout) -> Result<(), Box<dyn Error>> {
+	let (tile_width, tile_height) = layout.tile_size;
+	if tile_width == 0 || tile_height == 0 {
+		return Err("Sprite size can't be zero".into());
+	}
+	let expected_width = tile_width * layout.columns as u32;
+	let expected_height = tile_height * layout.rows as u32;
+	if (sheet_width, sheet_height) != (expected_width, expected_height) {
+		return Err(format!(
+			"Sheet is {}x{}px, but {} columns and {} rows of {}x{}px sprites need {}x{}px",
+			sheet_width, sheet_height, layout.columns, layout.rows, tile_width, tile_height, expected_width, expected_height
+		)
+		.into());
+	}
+	Ok(())
+}
@@ -1,3 +1,8 @@
+use std::{
+	collections::{HashMap, VecDeque},
+	error::Error,
+};
+
 #[rustfmt::skip]
 pub const ADJACENT_WITH_CENTER: [(isize, isize); 9] =
 	[
@@ -15,30 +20,158 @@ pub const ADJACENT_WITHOUT_CENTER: [(isize, isize); 8] =
 	];
 
 // Event Queue
+// Events are handed out in the order they were added.
 pub struct Events<E> {
-	events: Vec<E>,
+	events: VecDeque<E>,
+}
+
+// Anything which wants to react to events from an event queue, without the queue's owner
+// needing to know about it. Subscribers only get to look at each event; the queue keeps ownership.
+pub trait Subscriber<E> {
+	fn notify(&mut self, event: &E);
 }
 
 impl<E> Events<E> {
 	pub fn add(&mut self, event: E) {
-		self.events.push(event);
+		self.events.push_back(event);
 	}
 
-	pub fn next(&mut self) -> Option<E> {
-		if !self.events.is_empty() {
-			self.events.pop()
-		} else {
-			None
-		}
+	// Takes the oldest event
+	pub fn pop(&mut self) -> Option<E> {
+		self.events.pop_front()
 	}
 	pub fn clear(&mut self) {
 		self.events.clear();
 	}
+	pub fn len(&self) -> usize {
+		self.events.len()
+	}
+	pub fn is_empty(&self) -> bool {
+		self.events.is_empty()
+	}
+	// Looks at the queued events without taking them
+	pub fn iter(&self) -> impl Iterator<Item = &E> {
+		self.events.iter()
+	}
+	// Empties the queue, giving each event to every subscriber in the order they are listed.
+	pub fn dispatch(&mut self, subscribers: &mut [&mut dyn Subscriber<E>]) {
+		while let Some(event) = self.pop() {
+			for subscriber in subscribers.iter_mut() {
+				subscriber.notify(&event);
+			}
+		}
+	}
 }
 impl<E> Default for Events<E> {
 	fn default() -> Self {
 		Self {
-			events: Vec::<E>::with_capacity(10),
+			events: VecDeque::<E>::with_capacity(10),
+		}
+	}
+}
+
+// Reads `key = value` lines, as used by theme manifests.
+// Blank lines and lines starting with `#` are ignored, and surrounding whitespace is trimmed.
+pub fn parse_key_values(text: &str) -> Result<HashMap<String, String>, Box<dyn Error>> {
+	let mut values = HashMap::new();
+	for (n, line) in text.lines().enumerate() {
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+		let (key, value) = line.split_once('=').ok_or(format!("Line {} is not in the form `key = value`", n + 1))?;
+		values.insert(key.trim().to_string(), value.trim().to_string());
+	}
+	Ok(values)
+}
+
+// Seed taken from the clock, for when no seed is given. Unlike macroquad's `get_time` this works without a window.
+pub fn time_seed() -> u64 {
+	(macroquad::miniquad::date::now() * 1000000.0) as u64
+}
+
+// Small seeded random number generator (SplitMix64), for generation which has to give the same
+// result for the same seed regardless of the order things are generated in.
+#[derive(Clone, Debug)]
+pub struct Rng {
+	state: u64,
+}
+
+impl Rng {
+	pub fn new(seed: u64) -> Self {
+		Self { state: seed }
+	}
+	// Generator for one part of a larger seeded whole, such as a chunk of a board
+	pub fn derive(seed: u64, a: u64, b: u64) -> Self {
+		let mut rng = Self::new(seed ^ a.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+		rng.state ^= rng.next_u64() ^ b.wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+		rng
+	}
+	pub fn next_u64(&mut self) -> u64 {
+		self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+		let mut z = self.state;
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+		z ^ (z >> 31)
+	}
+	// Number in `0..bound`. `bound` must not be zero.
+	pub fn below(&mut self, bound: usize) -> usize {
+		((self.next_u64() as u128 * bound as u128) >> 64) as usize
+	}
+	// True with the given probability
+	pub fn chance(&mut self, probability: f64) -> bool {
+		((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// Writes down every event it is given, along with its own name, in a log shared with other recorders
+	struct Recorder<'a> {
+		name: &'a str,
+		log: &'a std::cell::RefCell<Vec<(&'a str, u32)>>,
+	}
+
+	impl Subscriber<u32> for Recorder<'_> {
+		fn notify(&mut self, event: &u32) {
+			self.log.borrow_mut().push((self.name, *event));
 		}
 	}
+
+	#[test]
+	fn events_come_out_in_the_order_they_went_in() {
+		let mut events = Events::default();
+		// More than the queue starts with room for
+		for event in 0..25 {
+			events.add(event);
+		}
+		assert_eq!(events.iter().copied().collect::<Vec<_>>(), (0..25).collect::<Vec<_>>());
+		assert_eq!(events.pop(), Some(0));
+		assert_eq!(events.pop(), Some(1));
+		events.add(25);
+		let rest: Vec<_> = std::iter::from_fn(|| events.pop()).collect();
+		assert_eq!(rest, (2..26).collect::<Vec<_>>());
+		assert!(events.is_empty());
+	}
+
+	#[test]
+	fn dispatch_hands_every_event_to_every_subscriber_in_order() {
+		let log = std::cell::RefCell::new(vec![]);
+		let mut first = Recorder {
+			name: "first",
+			log: &log,
+		};
+		let mut second = Recorder {
+			name: "second",
+			log: &log,
+		};
+		let mut events = Events::default();
+		events.add(1);
+		events.add(2);
+		events.dispatch(&mut [&mut first, &mut second]);
+		assert_eq!(log.take(), [("first", 1), ("second", 1), ("first", 2), ("second", 2)]);
+		assert_eq!(events.len(), 0);
+	}
 }
@@ -0,0 +1,96 @@
+use std::{
+	collections::HashSet,
+	io::{BufRead, BufReader, Lines, Write},
+	net::{TcpListener, TcpStream},
+	thread,
+	time::Duration,
+};
+
+use minesweeper::net::{
+	coop::{CoopMessage, Intent},
+	coop_server,
+	protocol::LineMessage,
+};
+
+// A player speaking the co-op protocol directly, so every message the server sends can be checked
+struct Player {
+	stream: TcpStream,
+	lines: Lines<BufReader<TcpStream>>,
+}
+
+impl Player {
+	fn join(address: &str) -> Self {
+		let stream = TcpStream::connect(address).unwrap();
+		stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+		let lines = BufReader::new(stream.try_clone().unwrap()).lines();
+		Self { stream, lines }
+	}
+
+	fn send(&mut self, intent: Intent) {
+		writeln!(self.stream, "{}", intent).unwrap();
+	}
+
+	fn receive(&mut self) -> CoopMessage {
+		CoopMessage::parse(&self.lines.next().unwrap().unwrap()).unwrap()
+	}
+
+	// Messages up to and including the first one `last` accepts
+	fn receive_until(&mut self, last: impl Fn(&CoopMessage) -> bool) -> Vec<CoopMessage> {
+		let mut messages = vec![];
+		loop {
+			let message = self.receive();
+			messages.push(message);
+			if last(&message) {
+				return messages;
+			}
+		}
+	}
+}
+
+#[test]
+fn players_see_the_same_events_in_the_same_order() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let address = listener.local_addr().unwrap().to_string();
+	thread::spawn(move || coop_server::serve(listener, (16, 16, 99)).map_err(|error| error.to_string()));
+
+	let mut first = Player::join(&address);
+	assert_eq!(
+		first.receive(),
+		CoopMessage::Welcome {
+			player: 0,
+			width: 16,
+			height: 16,
+			mines: 99
+		}
+	);
+	let mut second = Player::join(&address);
+	assert!(matches!(second.receive(), CoopMessage::Welcome { player: 1, .. }));
+
+	// The second player flags a tile the first player's reveal left hidden, then the first starts a new board
+	first.send(Intent::Reveal(8, 8));
+	let mut seen_by_second = second.receive_until(|message| *message == CoopMessage::SweepDone);
+	assert_eq!(seen_by_second[0], CoopMessage::Reveal(8, 8, Some(0)));
+	let won = seen_by_second.contains(&CoopMessage::Win);
+	let revealed: HashSet<_> = seen_by_second
+		.iter()
+		.filter_map(|message| match *message {
+			CoopMessage::Reveal(x, y, _) => Some((x, y)),
+			_ => None,
+		})
+		.collect();
+	let (x, y) = (0..16).flat_map(|y| (0..16).map(move |x| (x, y))).find(|tile| !revealed.contains(tile)).unwrap();
+	// Flags can't be placed once the board is cleared, which a first click on a board this full hardly ever does
+	if !won {
+		second.send(Intent::Modify(x, y));
+		seen_by_second.extend(second.receive_until(|message| matches!(message, CoopMessage::Flag(..))));
+	}
+	first.send(Intent::Reset);
+	seen_by_second.extend(second.receive_until(|message| *message == CoopMessage::Reset));
+	let seen_by_first = first.receive_until(|message| *message == CoopMessage::Reset);
+	assert_eq!(seen_by_first, seen_by_second);
+	assert!(won || seen_by_first.contains(&CoopMessage::Flag(x, y, 1)));
+
+	// Whoever stays is told when someone leaves
+	drop(second);
+	assert_eq!(first.receive(), CoopMessage::Left(1));
+}
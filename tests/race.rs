@@ -0,0 +1,66 @@
+use std::{collections::HashSet, net::TcpListener, net::TcpStream, thread, time::Duration};
+
+use minesweeper::{
+	logic::{first_click::FirstClick, visible::VisibleTile, GameState, Minesweeper},
+	net::{
+		race::{self, Outcome, Race},
+		relay,
+	},
+};
+
+// Tiles the player revealed, which is everything their opponent should have been told about
+fn revealed(game: &Minesweeper) -> HashSet<(usize, usize)> {
+	let board = &game.board;
+	(0..board.height())
+		.flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+		.filter(|&(x, y)| {
+			matches!(
+				board.get_tile(x, y).map(|tile| tile.visible()),
+				Some(VisibleTile::Revealed(_) | VisibleTile::Exploded)
+			)
+		})
+		.collect()
+}
+
+fn outcome(game: &Minesweeper) -> Outcome {
+	match game.state {
+		GameState::Victory => Outcome::Won(0.0),
+		GameState::GameOver => Outcome::Lost,
+		_ => Outcome::Left,
+	}
+}
+
+#[test]
+fn headless_players_race_on_the_same_board() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let address = listener.local_addr().unwrap().to_string();
+	thread::spawn(move || relay::serve(listener, (9, 9, 10), FirstClick::default()));
+
+	// Someone who gives up waiting mustn't be raced against
+	drop(TcpStream::connect(&address).unwrap());
+	thread::sleep(Duration::from_millis(100));
+
+	let players: Vec<_> = (0..2)
+		.map(|_| {
+			let address = address.clone();
+			thread::spawn(move || race::play_headless(&address).unwrap())
+		})
+		.collect();
+	let results: Vec<(Race, Minesweeper)> = players.into_iter().map(|player| player.join().unwrap()).collect();
+
+	let (first_race, first) = &results[0];
+	let (second_race, second) = &results[1];
+	assert!(first.board.seed.is_some());
+	assert_eq!(first.board.seed, second.board.seed);
+	assert_eq!(first.board.shown_text(), second.board.shown_text());
+	assert!(first.board.shown_text().is_some());
+	for ((race, _), (_, opponent)) in [(&results[0], &results[1]), (&results[1], &results[0])] {
+		assert_eq!(race.opponent.revealed, revealed(opponent));
+		let told = race.opponent.outcome.map(|outcome| match outcome {
+			Outcome::Won(_) => Outcome::Won(0.0),
+			outcome => outcome,
+		});
+		assert_eq!(told, Some(outcome(opponent)));
+	}
+	assert!(!first_race.opponent.revealed.is_empty() && !second_race.opponent.revealed.is_empty());
+}